@@ -1,33 +1,40 @@
-use std::f64::{EPSILON, MIN_POSITIVE};
-use std::f64::consts::PI;
+#![allow(clippy::excessive_precision)]
+
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_2_PI, FRAC_2_SQRT_PI, FRAC_PI_2, LN_2, PI, SQRT_2};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 // =============================================================================
 // Constants
 // =============================================================================
-const EPS: f64 = EPSILON;
-const FPMIN: f64 = MIN_POSITIVE / EPS;
-const G: f64 = 5f64;
-const N: usize = 7;
-const ASWITCH: usize = 100;
-const NGAU: usize = 18;
+const EPS: f64 = f64::EPSILON;
+const FPMIN: f64 = f64::MIN_POSITIVE / EPS;
+const ASWITCH: f64 = 100f64;
+const TEMME_SIGMA: f64 = 0.4;
+// Gauss-Legendre abscissas and weights on [0, 2], first half of the symmetric 36-point rule
 const Y: [f64; 18] = [
-    0.0021695375159141994, 0.011413521097787704, 0.027972308950302116,
-    0.051727015600492421, 0.082502225484340941, 0.12007019910960293,
-    0.16415283300752470, 0.21442376986779355, 0.27051082840644336, 
-    0.33199876341447887, 0.39843234186401943, 0.46931971407375483, 
-    0.54413605556657973, 0.62232745288031077, 0.70331500465597174, 
-    0.78649910768313447, 0.87126389619061517, 0.95698180152629142
+    0.0021695375159141638, 0.011413521097787762, 0.027972308950302051,
+    0.051727015600492455, 0.082502225484340934, 0.12007019910960287,
+    0.16415283300752469, 0.21442376986779349, 0.27051082840644342,
+    0.33199876341447894, 0.39843234186401946, 0.46931971407375484,
+    0.54413605556657973, 0.62232745288031078, 0.70331500465597173,
+    0.78649910768313442, 0.87126389619061521, 0.95698180152629139
 ];
 const W: [f64; 18] = [
-    0.0055657196642445571, 0.012915947284065419, 0.020181515297735382,
-    0.027298621498568734, 0.034213810770299537, 0.040875750923643261,
-    0.047235083490265582, 0.053244713977759692, 0.058860144245324798,
-    0.064039797355015485, 0.068745323835736408, 0.072941885005653087,
-    0.076598410645870640, 0.079687828912071670, 0.082187266704339706,
-    0.084078218979661945, 0.085346685739338721, 0.085983275670394821
+    0.0055657196642450454, 0.012915947284065574, 0.020181515297735472,
+    0.027298621498568779, 0.03421381077030723, 0.040875750923644895,
+    0.047235083490265978, 0.053244713977759919, 0.058860144245324817,
+    0.06403979735501549, 0.068745323835736443, 0.072941885005653061,
+    0.076598410645870675, 0.079687828912071602, 0.08218726670433971,
+    0.084078218979661935, 0.085346685739338627, 0.085983275670394747
 ];
 // Error functions
 const NCOEF: usize = 28;
+const ERFCX_CF: f64 = 10f64;
+const ERFCX_ASYMPTOTIC: f64 = 1e8;
+const FADDEEVA_TAYLOR: f64 = 0.1;
+const FADDEEVA_ASYMPTOTIC: f64 = 1e8;
+// Fresnel integrals
+const FRESNEL_XMIN: f64 = 1.5;
 const COF: [f64; 28] = [
     -1.3026537197817094, 6.4196979235649026e-1,
     1.9476473204185836e-2, -9.561514786808631e-3,
@@ -44,20 +51,162 @@ const COF: [f64; 28] = [
     -1.523e-15, -9.4e-17,
     1.21e-16,-2.8e-17
 ];
+// Dawson function : Chebyshev series in 2x^2 - 1 (x <= 1), (2x^2 - 17) / 15 (x <= 4) and 32 / x^2 - 1
+const DAWSON_SMALL: [f64; 13] = [
+    -6.3517343751459492e-3, -2.2940714796773869e-1, 2.2130500939084764e-2,
+    -1.549265453892985e-3, 8.4973277156849175e-5, -3.8282662709720149e-6,
+    1.4628548062501632e-7, -4.8519823818259918e-9, 1.4214635777591398e-10,
+    -3.7288360879205965e-12, 8.8549429617782034e-14, -1.9207571313502064e-15,
+    3.8343258672463276e-17
+];
+const DAWSON_MID: [f64; 31] = [
+    1.1106452514436286, -4.8664674386908822e-2, 5.6438074553427081e-3,
+    1.5600011357085643e-2, -2.0127246103941354e-2, 1.6142561932391143e-2,
+    -1.0173417134426082e-2, 5.4050556918382325e-3, -2.5033057303275103e-3,
+    1.031195824444869e-3, -3.8308274542710349e-4, 1.2967906319315438e-4,
+    -4.0330447982294876e-5, 1.160118426680865e-5, -3.1040955909552754e-6,
+    7.7631560302079142e-7, -1.8224031887804368e-7, 4.0305586986383435e-8,
+    -8.4261897876480385e-9, 1.6700210454437583e-9, -3.1462212512974984e-10,
+    5.6477575727042136e-11, -9.6812439455417115e-12, 1.5878949735252772e-12,
+    -2.4965688012644164e-13, 3.7690261667035924e-14, -5.4721192097705122e-15,
+    7.6515609684181879e-16, -1.0318001363153934e-16, 1.343492620688711e-17,
+    -1.691127638976889e-18
+];
+const DAWSON_LARGE: [f64; 30] = [
+    1.6904856377657038e-2, 8.683252278406958e-3, 2.4248640424177155e-4,
+    1.26118239957269e-5, 1.066453314636177e-6, 1.3581597947907276e-7,
+    2.1710423565772984e-8, 2.8670105018052953e-9, -1.9013363930358201e-10,
+    -3.0977804843952011e-10, -1.0294148760575092e-10, -6.2603564594595762e-12,
+    8.5631324974464512e-12, 3.0330451480756593e-12, -2.5236183068092914e-13,
+    -4.2106047954406645e-13, -4.4311408266462383e-14, 4.9112102728412052e-14,
+    1.2358562422839034e-14, -5.7887331990165692e-15, -2.2827232948073586e-15,
+    7.6371494110141265e-16, 3.8515468835668117e-16, -1.1999320569282906e-16,
+    -6.3134391500945723e-17, 2.2395599659729754e-17, 9.987925830076496e-18,
+    -4.6810682743224953e-18, -1.4363036443497213e-18, 1.0208227314105411e-18
+];
+
+// Normal distribution : Phi(-x) underflows beyond NORM_TAIL_MAX
+const NORM_TAIL_MAX: f64 = 40f64;
+// Normal quantile : Wichura (1988), AS241 PPND16
+const AS241_A: [f64; 8] = [
+    3.3871328727963666080e0, 1.3314166789178437745e+2, 1.9715909503065514427e+3,
+    1.3731693765509461125e+4, 4.5921953931549871457e+4, 6.7265770927008700853e+4,
+    3.3430575583588128105e+4, 2.5090809287301226727e+3
+];
+const AS241_B: [f64; 8] = [
+    1f64, 4.2313330701600911252e+1, 6.8718700749205790830e+2,
+    5.3941960214247511077e+3, 2.1213794301586595867e+4, 3.9307895800092710610e+4,
+    2.8729085735721942674e+4, 5.2264952788528545610e+3
+];
+const AS241_C: [f64; 8] = [
+    1.42343711074968357734e0, 4.63033784615654529590e0, 5.76949722146069140550e0,
+    3.64784832476320460504e0, 1.27045825245236838258e0, 2.41780725177450611770e-1,
+    2.27238449892691845833e-2, 7.74545014278341407640e-4
+];
+const AS241_D: [f64; 8] = [
+    1f64, 2.05319162663775882187e0, 1.67638483018380384940e0,
+    6.89767334985100004550e-1, 1.48103976427480074590e-1, 1.51986665636164571966e-2,
+    5.47593808499534494600e-4, 1.05075007164441684324e-9
+];
+const AS241_E: [f64; 8] = [
+    6.65790464350110377720e0, 5.46378491116411436990e0, 1.78482653991729133580e0,
+    2.96560571828504891230e-1, 2.65321895265761230930e-2, 1.24266094738807843860e-3,
+    2.71155556874348757815e-5, 2.01033439929228813265e-7
+];
+const AS241_F: [f64; 8] = [
+    1f64, 5.99832206555887937690e-1, 1.36929880922735805310e-1,
+    1.48753612908506148525e-2, 7.86869131145613259100e-4, 1.84631831751005468180e-5,
+    1.42151175831644588870e-7, 2.04426310338993978564e-15
+];
+// Bessel functions : Chebyshev series in x^2 / 32 - 1 (x <= 8) and 128 / x^2 - 1 (Hankel P, 8 Q / x)
+const BESSEL_CHEB_MAX: f64 = 8f64;
+const BESSEL_ASYM_MIN: f64 = 25f64;
+const BESSEL_TEMME_MAX: f64 = 2f64;
+const BESSEL_MILLER_ACC: f64 = 160f64;
+const BESSEL_RESCALE: f64 = 1e10;
+// First zeros of J_0 and J_1 as hi + lo
+const BESSEL_J0_ZEROS: [(f64, f64); 2] = [
+    (2.404825557695773, -1.176691651530894e-16), (5.520078110286311, 8.088597146146722e-17)
+];
+const BESSEL_J1_ZEROS: [(f64, f64); 2] = [
+    (3.8317059702075125, -1.5269184090088067e-16), (7.015586669815619, -9.414165653410389e-17)
+];
+const BESSEL_J0_SMALL: [f64; 15] = [
+    3.9807110003202971e-3, -2.6209391384970251e-3, 8.6877214720625765e-4,
+    -1.7040584951557358e-4, 2.204908639454817e-5, -2.0248894980012835e-6,
+    1.3898025173428905e-7, -7.4067012543441668e-9, 3.1559927583611333e-10,
+    -1.1003860827939698e-11, 3.1989232970233478e-13, -7.8750412547091548e-15,
+    1.6632588015211278e-16, -3.0475993756698375e-18, 4.8912290055811177e-20
+];
+const BESSEL_Y0_SMALL: [f64; 18] = [
+    -6.6292226406569883e-2, -2.7447430552974527e-1, 1.7903431407718266e-1,
+    2.6156734625504664e-1, -1.7730201278114358e-1, 4.7196689595763387e-2,
+    -7.2879624795520792e-3, 7.5311359325777423e-4, -5.632079141056987e-5,
+    3.206532537654801e-6, -1.4407233274018699e-7, 5.2487947873305161e-9,
+    -1.5837552541812015e-10, 4.0263308183061208e-12, -8.7473412033107696e-14,
+    1.6434898714919469e-15, -2.6977881152566836e-17, 3.9032584173475983e-19
+];
+const BESSEL_J1_SMALL: [f64; 15] = [
+    5.5791419775226207e-4, -3.1156840366435618e-4, 8.5536465885405378e-5,
+    -1.4149356425623484e-5, 1.5764700992258891e-6, -1.2690755023694251e-7,
+    7.7476140770730569e-9, -3.7165771466757722e-10, 1.4395417669312554e-11,
+    -4.6000782241345199e-13, 1.2341465589619717e-14, -2.8205466239524857e-16,
+    5.558821065951572e-18, -9.5469308657552193e-20, 1.4418291781315051e-21
+];
+const BESSEL_Y1_SMALL: [f64; 18] = [
+    5.0760264714835635e-3, -1.608717304766875e-2, -9.5912045360830742e-2,
+    8.4451972596523458e-2, -2.8328123944594366e-2, 5.289897544167113e-3,
+    -6.414551451326356e-4, 5.5059828733387438e-5, -3.5380800186893499e-6,
+    1.7707804556154403e-7, -7.1105500498992798e-9, 2.3443379059115164e-10,
+    -6.4651518414115942e-12, 1.51429151205002e-13, -3.0511859694473253e-15,
+    5.3466803857286701e-17, -8.2249113720215846e-19, 1.1198518333682238e-20
+];
+const BESSEL_P0: [f64; 14] = [
+    1.9989206986950373, -5.3652204681321174e-4, 3.0751847875194746e-6,
+    -5.1705945376060977e-8, 1.6306464635151383e-9, -7.86409137723707e-11,
+    5.1682623873491925e-12, -4.3045788699253912e-13, 4.3265957431549406e-14,
+    -5.0690340959352361e-15, 6.7480722157338737e-16, -1.0011513723467786e-16,
+    1.6305919233744185e-17, -2.8808661694828712e-18
+];
+const BESSEL_Q0: [f64; 16] = [
+    -3.1111709210674018e-2, 6.8385199426116496e-5, -7.4144984110606473e-7,
+    1.7972457247968992e-8, -7.27191593686632e-10, 4.2201219046687384e-11,
+    -3.2067474209966347e-12, 3.0061451253517063e-13, -3.336328185322427e-14,
+    4.2552250402454611e-15, -6.09993013164005e-16, 9.6621289703032567e-17,
+    -1.6686065214378146e-17, 3.1082440486738144e-18, -6.1911157873581449e-19,
+    1.3091448717220122e-19
+];
+const BESSEL_P1: [f64; 14] = [
+    2.0018060817200274, 8.9898983308594086e-4, -3.9872843004889085e-6,
+    6.1776339606442985e-8, -1.8718907491063066e-9, 8.8168986595823389e-11,
+    -5.7048636403956447e-12, 4.6991955152305424e-13, -4.6842237839904892e-14,
+    5.4526748960447172e-15, -7.2211808422740179e-16, 1.0667689114335412e-16,
+    -1.7312313216116335e-17, 3.0492991197665872e-18
+];
+const BESSEL_Q1: [f64; 16] = [
+    9.355557413907065e-2, -9.6277235491570793e-5, 9.1386152579554541e-7,
+    -2.0959781384083422e-8, 8.2291933276505541e-10, -4.6863636881769452e-11,
+    3.5152187949686081e-12, -3.2643156743278999e-13, 3.5967765829165292e-14,
+    -4.5612523950772972e-15, 6.508282957783384e-16, -1.0269147531823243e-16,
+    1.7676355487764792e-17, -3.2834519872981615e-18, 6.5240811495892603e-19,
+    -1.3765771484849488e-19
+];
 // Incomplete beta function
-const SWITCH: usize = 3000;
+const CF_MAX_ITER: usize = 1000000;
 
 // =============================================================================
 // Incomplete Gamma function
 // =============================================================================
 /// Incomplete Gamma function P(a,x)
+///
+/// For `a >= 100` and `|x - a| <= 0.4 a` Temme's uniform asymptotic expansion is used.
 pub fn gammp(a: f64, x: f64) -> f64 {
     assert!(x >= 0f64 && a > 0f64, "Bad args in gammp");
     if x == 0f64 {
         0f64
-    } else if (a as usize) >= ASWITCH {
-        // Quadrature
-        gammpapprox(a,x,IncGamma::P)
+    } else if a >= ASWITCH && ((x - a) / a).abs() <= TEMME_SIGMA {
+        // Uniform asymptotic expansion
+        gamma_temme(a,x,IncGamma::P)
     } else if x < a + 1f64 {
         // Series representation
         gser(a,x)
@@ -68,13 +217,18 @@ pub fn gammp(a: f64, x: f64) -> f64 {
 }
 
 /// Incomplete Gamma function Q(a,x)
+///
+/// For `a >= 100` and `|x - a| <= 0.4 a` Temme's uniform asymptotic expansion is used.
 pub fn gammq(a: f64, x: f64) -> f64 {
     assert!(x >= 0f64 && a > 0f64, "Bad args in gammp");
     if x == 0f64 {
         1f64
-    } else if (a as usize) >= ASWITCH {
-        // Quadrature
-        gammpapprox(a,x,IncGamma::Q)
+    } else if a >= ASWITCH && ((x - a) / a).abs() <= TEMME_SIGMA {
+        // Uniform asymptotic expansion
+        gamma_temme(a,x,IncGamma::Q)
+    } else if x < a + 1f64 && a < 1f64 {
+        // P is close to 1 for small a, so use the series of Gamma(a,x) directly
+        upper_gamma_small(a, x) / gamma(a)
     } else if x < a + 1f64 {
         // Series representation
         1f64 - gser(a,x)
@@ -84,9 +238,35 @@ pub fn gammq(a: f64, x: f64) -> f64 {
     }
 }
 
+/// Probability mass `P(a,x2) - P(a,x1)` of the Gamma distribution between two points
+///
+/// The difference is taken on the tail with the smaller value. If it still cancels, the density is
+/// integrated directly in `ln x`, so narrow intervals keep full relative precision.
+pub fn gammp_diff(a: f64, x1: f64, x2: f64) -> f64 {
+    assert!(x1 >= 0f64 && x2 >= 0f64 && a > 0f64, "Bad args in gammp_diff");
+    if x1 > x2 {
+        return -gammp_diff(a, x2, x1);
+    } else if x1 == x2 {
+        return 0f64;
+    }
+    let (p1, p2, q1, q2) = (gammp(a, x1), gammp(a, x2), gammq(a, x1), gammq(a, x2));
+    let (d, scale) = if p2 < q1 { (p2 - p1, p2) } else { (q1 - q2, q1) };
+    if d >= 0.5 * scale {
+        d
+    } else {
+        // P(a,x) = int x^a e^(-x) / Gamma(a) d(ln x), with u = ln(x / x1)
+        let l = ((x2 - x1) / x1).ln_1p();
+        gauss_legendre(|u| gamma_regularized_prefactor(a, x1 * u.exp()), 0f64, l)
+    }
+}
+
 /// Series expansion
 fn gser(a: f64, x: f64) -> f64 {
-    let gln = ln_gamma(a);
+    gser_sum(a, x) * gamma_regularized_prefactor(a, x)
+}
+
+/// Series of `gamma(a,x) e^x x^(-a)`
+fn gser_sum(a: f64, x: f64) -> f64 {
     let mut ap = a;
     let mut del = 1f64 / a;
     let mut sum = 1f64 / a;
@@ -95,14 +275,54 @@ fn gser(a: f64, x: f64) -> f64 {
         del *= x/ap;
         sum += del;
         if del.abs() < sum.abs() * EPS {
-            return sum * (-x + a * x.ln() - gln).exp();
+            return sum;
         }
     }
 }
 
 /// Continued Fraction
 fn gcf(a: f64, x: f64) -> f64 {
-    let gln = ln_gamma(a);
+    gamma_regularized_prefactor(a, x) * gcf_frac(a, x)
+}
+
+/// `x^a e^(-x) / Gamma(a)`
+///
+/// For small `a` the power is split by `gamma_prefactor`, as `exp(a ln(x) - x)` loses accuracy for large `x`.
+/// For large `a` this is `sqrt(a / 2pi) exp(a (ln(1 + s) - s) - correction(a))` with `s = (x - a) / a`,
+/// which avoids the cancellation between `a ln(x)`, `x` and `ln Gamma(a)`.
+fn gamma_regularized_prefactor(a: f64, x: f64) -> f64 {
+    if a < STIRLING_MIN {
+        gamma_prefactor(a, x) / gamma(a)
+    } else {
+        let s = (x - a) / a;
+        let e = if s.abs() <= 0.5 { a * ln_1p_mx(s) } else { a * (x / a).ln() - (x - a) };
+        (a / (2f64 * PI)).sqrt() * (e - stirling_correction(a)).exp()
+    }
+}
+
+/// `ln(1 + x) - x` without cancellation for small `x`
+fn ln_1p_mx(x: f64) -> f64 {
+    if x.abs() > 0.5 {
+        return x.ln_1p() - x;
+    }
+    // ln(1 + x) = 2 atanh(t) with t = x / (2 + x), and 2t - x = -x t
+    let t = x / (2f64 + x);
+    let t2 = t * t;
+    let mut tk = 1f64;
+    let mut sum = 1f64 / 3f64;
+    for k in 2 .. {
+        tk *= t2;
+        let del = tk / (2 * k + 1) as f64;
+        sum += del;
+        if del < sum * EPS {
+            break;
+        }
+    }
+    -x * t + 2f64 * t * t2 * sum
+}
+
+/// Continued fraction of `Gamma(a,x) e^x x^(-a)` (valid for every real `a`)
+fn gcf_frac(a: f64, x: f64) -> f64 {
     let mut b = x + 1f64 - a;
     let mut c = 1f64 / FPMIN;
     let mut d = 1f64 / b;
@@ -126,7 +346,7 @@ fn gcf(a: f64, x: f64) -> f64 {
             break;
         }
     }
-    (-x + a * x.ln() - gln).exp() * h
+    h
 }
 
 /// Kinds of Incomplete Gamma function
@@ -136,326 +356,3267 @@ enum IncGamma {
     Q
 }
 
-/// Gauss Legendre Quadrature (order of 18)
-fn gammpapprox(a: f64, x: f64, psig: IncGamma) -> f64 {
-    let a1 = a - 1f64;
-    let lna1 = a1.ln();
-    let sqrta1 = a1.sqrt();
-    let gln = ln_gamma(a);
-    let xu = if x > a1 {
-        (a1 + 11.5 * sqrta1).max(x + 6f64 * sqrta1)
-    } else {
-        0f64.max((a1 - 7.5 * sqrta1).min(x - 5f64 * sqrta1))
-    };
-    let mut sum = 0f64;
-    let mut t: f64;
-    for j in 0 .. NGAU {
-        t = x + (xu - x) * Y[j];
-        sum += W[j] * (-(t-a1) + a1*(t.ln() - lna1)).exp();
-    }
-    let ans = sum * (xu - x) * (a1 * (lna1 - 1f64).exp() - gln);
+/// Coefficients of Temme's expansion : `C_k(eta) = sum_n TEMME[k][n] eta^n`
+const TEMME: [&[f64]; 8] = [
+    &[
+        -0.33333333333333333, 0.083333333333333333, -0.014814814814814815,
+        0.0011574074074074074, 3.527336860670194e-4, -1.7875514403292181e-4,
+        3.9192631785224378e-5, -2.1854485106799922e-6, -1.85406221071516e-6,
+        8.296711340953086e-7, -1.7665952736826079e-7, 6.7078535434014986e-9,
+        1.0261809784240308e-8, -4.3820360184533532e-9, 9.1476995822367902e-10,
+        -2.551419399494625e-11, -5.8307721325504251e-11, 2.4361948020667416e-11,
+        -5.0276692801141756e-12, 1.1004392031956135e-13, 3.3717632624009854e-13
+    ],
+    &[
+        -0.0018518518518518519, -0.0034722222222222222, 0.0026455026455026455,
+        -9.9022633744855967e-4, 2.0576131687242798e-4, -4.0187757201646091e-7,
+        -1.8098550334489978e-5, 7.6491609160811101e-6, -1.6120900894563446e-6,
+        4.6471278028074343e-9, 1.378633446915721e-7, -5.752545603517705e-8,
+        1.1951628599778147e-8, -1.7543241719747648e-11, -1.0091543710600413e-9,
+        4.1627929918425826e-10, -8.5639070264929806e-11, 6.0672151016047586e-14,
+        7.1624989648114854e-12
+    ],
+    &[
+        0.0041335978835978836, -0.0026813271604938272, 7.7160493827160494e-4,
+        2.0093878600823045e-6, -1.0736653226365161e-4, 5.2923448829120125e-5,
+        -1.2760635188618728e-5, 3.4235787340961381e-8, 1.3721957309062933e-6,
+        -6.298992138380055e-7, 1.4280614206064242e-7, -2.0477098421990866e-10,
+        -1.4092529910867521e-8, 6.228974084922022e-9, -1.3670488396617113e-9,
+        9.4283561590146782e-13, 1.2872252400089318e-10
+    ],
+    &[
+        6.4943415637860082e-4, 2.2947209362139918e-4, -4.6918949439525571e-4,
+        2.6772063206283885e-4, -7.5618016718839764e-5, -2.3965051138672967e-7,
+        1.1082654115347302e-5, -5.6749528269915966e-6, 1.4230900732435884e-6,
+        -2.7861080291528142e-11, -1.6958404091930277e-7, 8.0994649053880824e-8,
+        -1.9111168485973654e-8
+    ],
+    &[
+        -8.618882909167117e-4, 7.8403922172006663e-4, -2.9907248030319018e-4,
+        -1.4638452578843418e-6, 6.6414982154651222e-5, -3.9683650471794347e-5,
+        1.1375726970678419e-5, 2.5074972262375328e-10, -1.6954149536558306e-6,
+        8.9075075322053097e-7, -2.2929348340008049e-7
+    ],
+    &[
+        -3.3679855336635815e-4, -6.9728137583658578e-5, 2.7727532449593921e-4,
+        -1.9932570516188848e-4, 6.7977804779372078e-5, 1.419062920643967e-7,
+        -1.3594048189768693e-5, 8.0184702563342015e-6, -2.2914811765080952e-6
+    ],
+    &[
+        5.3130793646399222e-4, -5.9216643735369388e-4, 2.7087820967180448e-4,
+        7.9023532326603279e-7, -8.1539693675619688e-5, 5.6116827531062497e-5,
+        -1.8329116582843376e-5
+    ],
+    &[
+        3.4436760689237767e-4, 5.1717909082605922e-5, -3.3493161081142236e-4,
+        2.812695154763237e-4
+    ]
+];
+
+/// Temme's uniform asymptotic expansion (for large `a` and `x` near `a`)
+///
+/// `Q(a,x) = erfc(eta sqrt(a/2)) / 2 + exp(-a eta^2 / 2) / sqrt(2 pi a) sum_k C_k(eta) a^(-k)`
+/// where `eta^2 / 2 = x/a - 1 - ln(x/a)` and `sign(eta) = sign(x - a)`.
+/// (DiDonato & Morris, ACM TOMS 12 (1986))
+fn gamma_temme(a: f64, x: f64, psig: IncGamma) -> f64 {
+    let sigma = (x - a) / a;
+    let phi = -ln_1p_mx(sigma);
+    let eta = (2f64 * phi).sqrt().copysign(sigma);
+    let sum = TEMME.iter().rev().fold(0f64, |acc, row| {
+        acc / a + row.iter().rev().fold(0f64, |c, &d| c * eta + d)
+    });
+    let r = (-a * phi).exp() / (2f64 * PI * a).sqrt() * sum;
+    let z = eta * (0.5 * a).sqrt();
     match psig {
-        IncGamma::P => {
-            if ans > 0f64 {
-                1f64 - ans
-            } else {
-                -ans
-            }
-        }
-        IncGamma::Q => {
-            if ans >= 0f64 {
-                ans
-            } else {
-                1f64 + ans
-            }
-        }
+        IncGamma::P => 0.5 * erfc(-z) - r,
+        IncGamma::Q => 0.5 * erfc(z) + r,
     }
 }
 
-/// Iunverse Incomplete Gamma function
+/// Inverse Incomplete Gamma function
+///
+/// Solves `P(a,x) = p` for `x`. Returns `+inf` for `p = 1`.
 pub fn invgammp(p: f64, a: f64) -> f64 {
-    let gln = ln_gamma(a);
-    let a1 = a - 1f64;
-    let lna1 = a1.ln();
-    let mut afac = 0f64;
-    let pp: f64;
-    let mut t: f64;
-
     assert!(a > 0f64, "a must be positive in invgammp");
     if p >= 1f64 {
-        return 100f64.max(a + 100f64 * a.sqrt());
+        return f64::INFINITY;
     } else if p <= 0f64 {
         return 0f64;
     }
+    invgamma_pq(p, 1f64 - p, a)
+}
+
+/// Inverse of upper Incomplete Gamma function
+///
+/// Solves `Q(a,x) = q` for `x` directly on the upper tail, so tiny `q` (e.g. `1e-20`) is not lost in `1 - q`.
+/// Returns `+inf` for `q = 0`.
+pub fn invgammq(q: f64, a: f64) -> f64 {
+    assert!(a > 0f64, "a must be positive in invgammq");
+    if q <= 0f64 {
+        return f64::INFINITY;
+    } else if q >= 1f64 {
+        return 0f64;
+    }
+    invgamma_pq(1f64 - q, q, a)
+}
+
+/// Solve `P(a,x) = p` or `Q(a,x) = q` (whichever is smaller)
+///
+/// Newton's method on `ln P` (or `ln Q`) as a function of `ln x`, which is nearly linear in both tails,
+/// so probabilities down to the smallest `f64` converge without overflow of the density.
+/// Safeguarded by bisection in `ln x` when a step leaves the bracket of the root.
+fn invgamma_pq(p: f64, q: f64, a: f64) -> f64 {
+    let gln = ln_gamma(a);
+    let upper = q < p;
+    let target = if upper { q } else { p };
+    let ln_target = target.ln();
 
     // Initial guess
-    let mut x = if a > 1f64 {
-        afac = (a1 * (lna1 - 1f64) - gln).exp();
-        pp = if p < 0.5 { p } else { 1f64 - p };
-        t = (-2f64 * pp.ln()).sqrt();
+    let x0 = if a > 1f64 {
+        let pp = if upper { q } else { p };
+        let t = (-2f64 * pp.ln()).sqrt();
         let mut x = (2.30753 + t * 0.27061)/(1f64 + t * (0.99229 + t * 0.04481)) - t;
-        if p < 0.5 { 
+        if !upper {
             x = -x;
         }
         1e-3_f64.max(a * (1f64 - 1f64 / (9f64 * a) - x / (3f64 * a.sqrt())).powi(3))
     } else {
-        t = 1f64 - a * (0.253 + a * 0.12);
+        let t = 1f64 - a * (0.253 + a * 0.12);
         if p < t {
             (p / t).powf(1f64 / a)
         } else {
-            1f64 - (1f64 - (p - t) / (1f64 - t)).ln()
+            1f64 - (q / (1f64 - t)).ln()
         }
     };
 
-    for _j in 0 .. 12 {
+    let mut lo = 0f64;
+    let mut hi = f64::INFINITY;
+    let mut x = x0;
+    for _j in 0 .. 100 {
         // x is too small to compute accurately
-        if x <= 0f64 {
-            return 0f64;
+        if x == 0f64 || x.is_infinite() {
+            return x;
         }
-        let err = gammp(a, x) - p;
-        t = if a > 1f64 {
-            afac * (-(x - a1) + a1 * (x.ln() - lna1)).exp()
+        // ln(P / p) and d ln P / d ln x = x^a e^(-x) / (Gamma(a) P),
+        // from the logarithm only if P underflows
+        let v = if upper { gammq(a, x) } else { gammp(a, x) };
+        let (f, mut slope) = if v.is_normal() {
+            ((v / target).ln(), gamma_regularized_prefactor(a, x) / v)
         } else {
-            (-x + a1 * x.ln() - gln).exp()
+            let lp = if upper { ln_upper_gamma(a, x) } else { ln_lower_gamma(a, x) } - gln;
+            (lp - ln_target, (a * x.ln() - x - gln - lp).exp())
         };
-        let u = err / t;
-        // Halley's method
-        t = u / (1f64 - 0.5 * 1f64.min(u * (a1 / x - 1f64)));
-        x -= t;
-        if x <= 0f64 {
-            x = 0.5 * (x + t);
-        }
-        if t.abs() < (x * EPS).max(EPS) {
+        if f == 0f64 {
+            break;
+        } else if (f > 0f64) != upper {
+            hi = x;
+        } else {
+            lo = x;
+        }
+        if upper {
+            slope = -slope;
+        }
+        let du = f / slope;
+        // Update ln x, but keep x itself to retain full relative precision
+        let xn = x * (-du).exp();
+        if du.abs() < 4f64 * EPS {
+            x = xn;
             break;
         }
+        // Bisection in ln x if Newton leaves the bracket
+        x = if xn > lo && xn < hi {
+            xn
+        } else if lo == 0f64 {
+            0.5 * hi
+        } else if hi.is_infinite() {
+            2f64 * lo
+        } else {
+            lo.sqrt() * hi.sqrt()
+        };
     }
     x
 }
 
-// =============================================================================
-// Lanczos approximation of Gamma
-// =============================================================================
-/// Lanczos g=5, n=7
-const LG5N7: [f64; 7] = [
-    1.000000000189712,
-    76.18009172948503,
-    -86.50532032927205,
-    24.01409824118972,
-    -1.2317395783752254,
-    0.0012086577526594748,
-    -0.00000539702438713199
-];
+/// Inverse Incomplete Gamma function with respect to `a`
+///
+/// Solves `P(a,x) = p` for `a`. Returns `0` for `p = 1` and `+inf` for `p = 0`.
+pub fn invgammp_a(p: f64, x: f64) -> f64 {
+    assert!(x > 0f64, "x must be positive in invgammp_a");
+    if p >= 1f64 {
+        return 0f64;
+    } else if p <= 0f64 {
+        return f64::INFINITY;
+    }
+    invgamma_a_pq(p, 1f64 - p, x)
+}
 
-/// Logarithm Gamma
-pub fn ln_gamma(z: f64) -> f64 {
-    let z = z - 1f64;
-    let base = z + G + 0.5;
-    let mut s = 0f64;
-    for i in 1 .. N {
-        s += LG5N7[i] / (z + i as f64);
+/// Inverse of upper Incomplete Gamma function with respect to `a`
+///
+/// Solves `Q(a,x) = q` for `a`. Returns `0` for `q = 0` and `+inf` for `q = 1`.
+pub fn invgammq_a(q: f64, x: f64) -> f64 {
+    assert!(x > 0f64, "x must be positive in invgammq_a");
+    if q <= 0f64 {
+        return 0f64;
+    } else if q >= 1f64 {
+        return f64::INFINITY;
     }
-    s += LG5N7[0];
-    (2f64 * PI).sqrt().ln() + s.ln() - base + base.ln() * (z + 0.5)
+    invgamma_a_pq(1f64 - q, q, x)
 }
 
-/// Gamma function
-pub fn gamma(z: f64) -> f64 {
-    if z > 1f64 {
-        let z_int = z as usize;
-        if z - (z_int as f64) == 0f64 {
-            return factorial(z_int-1) as f64;
+/// Solve `P(a,x) = p` or `Q(a,x) = q` (whichever is smaller) for `a`
+///
+/// `ln P` decreases and `ln Q` increases with `a`, so the root is bracketed from `a = x`
+/// and refined by Brent's method on the logarithm of the smaller tail.
+fn invgamma_a_pq(p: f64, q: f64, x: f64) -> f64 {
+    let upper = q < p;
+    let target = if upper { q } else { p };
+    let ln_target = target.ln();
+    let f = |a: f64| {
+        let v = if upper { gammq(a, x) } else { gammp(a, x) };
+        if v.is_normal() {
+            // ln(v / target) without the rounding of large logarithms
+            (v / target).ln()
+        } else if upper {
+            ln_upper_gamma(a, x) - ln_gamma(a) - ln_target
+        } else {
+            ln_lower_gamma(a, x) - ln_gamma(a) - ln_target
         }
-    }
+    };
+    monotone_root(f, x, upper)
+}
 
-    if z < 0.5 {
-        PI / ((PI * z).sin() * gamma(1f64 - z))
+/// Lower incomplete Gamma function `gamma(a,x)` (non-regularized)
+///
+/// Computed without forming `Gamma(a)` in the series region, so it does not overflow for large `a`
+/// as long as the result is finite.
+pub fn lower_gamma(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64 && a > 0f64, "Bad args in lower_gamma");
+    if x == 0f64 {
+        0f64
+    } else if x < a + 1f64 {
+        // Series representation
+        gser_sum(a, x) * gamma_prefactor(a, x)
     } else {
-        ln_gamma(z).exp()
+        // gamma(a,x) = Gamma(a) - Gamma(a,x)
+        let g = gamma(a);
+        if g.is_finite() {
+            g - gcf_frac(a, x) * gamma_prefactor(a, x)
+        } else {
+            ln_lower_gamma(a, x).exp()
+        }
     }
 }
 
-// =============================================================================
-// Beta function
-// =============================================================================
-/// Beta function
-pub fn beta(z: f64, w: f64) -> f64 {
-    (ln_gamma(z) + ln_gamma(w) - ln_gamma(z+w)).exp()
+/// Upper incomplete Gamma function `Gamma(a,x)` (non-regularized)
+///
+/// Valid for every real `a` when `x > 0`, e.g. `Gamma(0,x) = E1(x)`.
+/// `Gamma(a,0) = Gamma(a)` for `a > 0` and `+inf` for `a <= 0`.
+pub fn upper_gamma(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64, "Bad x in upper_gamma");
+    if x == 0f64 {
+        if a > 0f64 { gamma(a) } else { f64::INFINITY }
+    } else if (a > 0f64 && x >= a + 1f64) || (a <= 0f64 && x >= 1.5) {
+        // Continued fraction representation
+        gcf_frac(a, x) * gamma_prefactor(a, x)
+    } else if a >= 1f64 {
+        // Gamma(a,x) = Gamma(a) - gamma(a,x)
+        let g = gamma(a);
+        if g.is_finite() {
+            g - gser_sum(a, x) * gamma_prefactor(a, x)
+        } else {
+            ln_upper_gamma(a, x).exp()
+        }
+    } else if a >= -0.5 {
+        upper_gamma_small(a, x)
+    } else {
+        // Gamma(b-1,x) = (Gamma(b,x) - x^(b-1) e^(-x)) / (b-1)
+        let n = (-a).round();
+        let mut b = a + n;
+        let mut g = upper_gamma_small(b, x);
+        for _ in 0 .. n as usize {
+            b -= 1f64;
+            g = (g - gamma_prefactor(b, x)) / b;
+        }
+        g
+    }
 }
 
-
-// =============================================================================
-// Error functions
-// =============================================================================
-/// Error function
-pub fn erf(x: f64) -> f64 {
-    if x >= 0f64 {
-        1.0 - erfccheb(x)
+/// Logarithm of lower incomplete Gamma function `ln gamma(a,x)`
+pub fn ln_lower_gamma(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64 && a > 0f64, "Bad args in ln_lower_gamma");
+    if x == 0f64 {
+        f64::NEG_INFINITY
+    } else if x < a + 1f64 {
+        // Series representation
+        gser_sum(a, x).ln() - x + a * x.ln()
     } else {
-        erfccheb(-x) - 1f64
+        // gamma(a,x) = Gamma(a) (1 - Q(a,x))
+        ln_gamma(a) + (-gcf(a, x)).ln_1p()
     }
 }
 
-/// Complementary error function
-pub fn erfc(x: f64) -> f64 {
-    if x >= 0f64 {
-        erfccheb(x)
+/// Logarithm of upper incomplete Gamma function `ln Gamma(a,x)`
+///
+/// Valid for every real `a` when `x > 0`.
+pub fn ln_upper_gamma(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64, "Bad x in ln_upper_gamma");
+    if x == 0f64 {
+        if a > 0f64 { ln_gamma(a) } else { f64::INFINITY }
+    } else if (a > 0f64 && x >= a + 1f64) || (a <= 0f64 && x >= 1.5) {
+        // Continued fraction representation
+        gcf_frac(a, x).ln() - x + a * x.ln()
+    } else if a >= 1f64 {
+        // Gamma(a,x) = Gamma(a) (1 - P(a,x))
+        ln_gamma(a) + (-gser(a, x)).ln_1p()
     } else {
-        2f64 - erfccheb(-x)
+        upper_gamma(a, x).ln()
     }
 }
 
-/// Chebyshev coefficients
-fn erfccheb(z: f64) -> f64 {
-    let mut d = 0f64;
-    let mut dd = 0f64;
-
-    assert!(z >= 0f64, "erfccheb requires nonnegative argument");
-    let t = 2f64 / (2f64 + z);
-    let ty = 4f64 * t - 2f64;
-    for j in (1 .. NCOEF-1).rev() {
-        let tmp = d;
-        d = ty * d - dd + COF[j];
-        dd = tmp;
+/// `x^a e^(-x)` without spurious overflow
+fn gamma_prefactor(a: f64, x: f64) -> f64 {
+    let p = x.powf(0.5 * a);
+    let e = (-0.5 * x).exp();
+    if p.is_finite() && p > 0f64 && e > 0f64 {
+        (p * e) * (e * p)
+    } else {
+        (a * x.ln() - x).exp()
     }
-    t * (-z.powi(2) + 0.5 * (COF[0] + ty * d) - dd).exp()
 }
 
-/// Inverse of complementary error function
-pub fn inverfc(p: f64) -> f64 {
-    // Return arbitrary large pos or neg value
-    if p >= 2f64 {
-        return -100f64;
-    } else if p <= 0f64 {
-        return 100f64;
+/// `Gamma(a,x)` for `-1/2 <= a < 1` and `x < a + 1` (or `x < 3/2`)
+///
+/// `Gamma(a,x) = (Gamma(1+a) - 1) / a - (x^a - 1) / a - x^a sum_(k>=1) (-x)^k / (k! (a + k))`
+/// which has no cancellation as `a -> 0` (`Gamma(0,x) = E1(x)`).
+fn upper_gamma_small(a: f64, x: f64) -> f64 {
+    let lnx = x.ln();
+    let (g1, u) = if a == 0f64 {
+        (ONE_MINUS_EULER - 1f64, lnx)
+    } else {
+        // ln Gamma(1+a) = ln Gamma(2+a) - ln(1+a) keeps full accuracy for tiny a
+        let lg1 = if a.abs() <= 0.5 { ln_gamma_2p(a) - a.ln_1p() } else { ln_gamma(1f64 + a) };
+        (lg1.exp_m1() / a, (a * lnx).exp_m1() / a)
+    };
+    let mut term = 1f64;
+    let mut sum = 0f64;
+    for k in 1 .. {
+        term *= -x / k as f64;
+        let del = term / (a + k as f64);
+        sum += del;
+        if del.abs() < sum.abs() * EPS {
+            break;
+        }
     }
+    g1 - u - (a * lnx).exp() * sum
+}
 
-    let pp = if p < 1f64 { p } else { 2f64 - p };
-    let t = (-2f64 * (pp / 2f64).ln()).sqrt();
-    let mut x = -0.70711 * ((2.30753 + t * 0.27061) / (1f64 + t * (0.99229 + t * 0.04481)) - t);
-    for _j in 0 .. 2 {
-        let err = erfc(x) - pp;
-        x += err / (1.12837916709551257 * (-x.powi(2)).exp() - x * err);
+/// Derivative of the regularized incomplete Gamma function with respect to `x`
+///
+/// `d/dx P(a,x) = x^(a-1) e^(-x) / Gamma(a)`
+pub fn gammp_dx(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64 && a > 0f64, "Bad args in gammp_dx");
+    if x == 0f64 {
+        if a < 1f64 { f64::INFINITY } else if a == 1f64 { 1f64 } else { 0f64 }
+    } else {
+        gamma_regularized_prefactor(a, x) / x
     }
-    if p < 1f64 {
-        x
+}
+
+/// Derivative of the regularized incomplete Gamma function with respect to `a`
+///
+/// Differentiates the series (`x < a + 1`) or the continued fraction term by term,
+/// so `d/da Q(a,x) = -gammp_da(a,x)` is obtained without cancellation.
+pub fn gammp_da(a: f64, x: f64) -> f64 {
+    assert!(x >= 0f64 && a > 0f64, "Bad args in gammp_da");
+    if x == 0f64 {
+        0f64
+    } else if x < a + 1f64 {
+        // P = x^a e^(-x) / Gamma(a+1) sum_n x^n / (a+1)_n
+        let mut term = 1f64;
+        let mut sum = 1f64;
+        let mut dterm = 0f64;
+        let mut dsum = 0f64;
+        let mut ap = a;
+        loop {
+            ap += 1f64;
+            dterm = (dterm - term / ap) * x / ap;
+            term *= x / ap;
+            sum += term;
+            dsum += dterm;
+            if term < sum * EPS && dterm.abs() < dsum.abs() * EPS {
+                break;
+            }
+        }
+        let lnpsi = (x / (a + 1f64)).ln() + ln_minus_digamma(a + 1f64);
+        gamma_regularized_prefactor(a, x) / a * (sum * lnpsi + dsum)
     } else {
-        -x
+        // Q = x^a e^(-x) / Gamma(a) F(a,x) with F the continued fraction of gcf_frac
+        let (f, df) = cf_derivative(|n| {
+            if n == 1 {
+                (1f64, 0f64, x + 1f64 - a, -1f64)
+            } else {
+                let i = (n - 1) as f64;
+                (-i * (i - a), i, x + 2f64 * i + 1f64 - a, -1f64)
+            }
+        });
+        let lnpsi = (x / a).ln() + ln_minus_digamma(a);
+        -gamma_regularized_prefactor(a, x) * (f * lnpsi + df)
     }
 }
 
-pub fn inverf(p: f64) -> f64 {
-    inverfc(1f64 - p)
+/// Continued fraction `a_1 / (b_1 + a_2 / (b_2 + ...))` and its derivative
+///
+/// `term(n)` returns `(a_n, da_n, b_n, db_n)` for `n >= 1`.
+/// Evaluated by the forward recurrence, differentiated term by term and rescaled at every step.
+fn cf_derivative<F: Fn(usize) -> (f64, f64, f64, f64)>(term: F) -> (f64, f64) {
+    // (A_(n-2), A_(n-1)), (B_(n-2), B_(n-1)) and their derivatives
+    let (mut a0, mut a1) = (1f64, 0f64);
+    let (mut b0, mut b1) = (0f64, 1f64);
+    let (mut da0, mut da1) = (0f64, 0f64);
+    let (mut db0, mut db1) = (0f64, 0f64);
+    let mut f = 0f64;
+    let mut df = 0f64;
+    for n in 1 .. CF_MAX_ITER {
+        let (an, dan, bn, dbn) = term(n);
+        let a2 = bn * a1 + an * a0;
+        let b2 = bn * b1 + an * b0;
+        let da2 = dbn * a1 + bn * da1 + dan * a0 + an * da0;
+        let db2 = dbn * b1 + bn * db1 + dan * b0 + an * db0;
+        a0 = a1;
+        b0 = b1;
+        da0 = da1;
+        db0 = db1;
+        a1 = a2;
+        b1 = b2;
+        da1 = da2;
+        db1 = db2;
+        if b1 != 0f64 {
+            let r = 1f64 / b1;
+            a0 *= r;
+            b0 *= r;
+            da0 *= r;
+            db0 *= r;
+            a1 *= r;
+            b1 = 1f64;
+            da1 *= r;
+            db1 *= r;
+            let fnew = a1;
+            let dfnew = da1 - fnew * db1;
+            let done = (fnew - f).abs() <= EPS * fnew.abs() && (dfnew - df).abs() <= EPS * dfnew.abs();
+            f = fnew;
+            df = dfnew;
+            if done {
+                break;
+            }
+        }
+    }
+    (f, df)
 }
 
 // =============================================================================
-// Incomplete Beta function
+// Gamma function
 // =============================================================================
-pub fn betai(a: f64, b: f64, x: f64) -> f64 {
-    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betai");
-    assert!(x >= 0f64 && x <= 1f64, "Bad x in routine betai");
-    if x == 0f64 || x == 1f64 {
-        return x;
-    }
-    let switch = SWITCH as f64;
-    if a > switch && b > switch {
-        return betaiapprox(a, b, x);
-    }
-    let bt = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1f64 - x).ln()).exp();
-    if x < (a + 1f64) / (a + b*2f64) {
-        bt * betacf(a, b, x) / a
+/// Taylor coefficients of ln Gamma(2 + e) : (-1)^k (zeta(k) - 1) / k for k = 2, 3, ...
+const LN_GAMMA_2P: [f64; 28] = [
+    0.32246703342411322, -0.067352301053198095,
+    0.020580808427784548, -0.0073855510286739853,
+    0.0028905103307415233, -0.001192753911703261,
+    0.00050966952474304242, -0.00022315475845357938,
+    9.9457512781808534e-5, -4.4926236738133142e-5,
+    2.0507212775670692e-5, -9.4394882752683959e-6,
+    4.3748667899074878e-6, -2.0392157538013662e-6,
+    9.5514121304074198e-7, -4.492469198764566e-7,
+    2.1207184805554666e-7, -1.00432248239681e-7,
+    4.7698101693639806e-8, -2.2711094608943165e-8,
+    1.0838659214896954e-8, -5.1834750419700467e-9,
+    2.4836745438024783e-9, -1.1921401405860912e-9,
+    5.731367241678862e-10, -2.7595228851242331e-10,
+    1.3304764374244489e-10, -6.4229645638381e-11
+];
+
+/// Abscissa of the minimum of Gamma on the positive axis, rounded to double
+const LN_GAMMA_XMIN: f64 = 1.4616321449683622;
+
+/// Taylor coefficients of ln Gamma(LN_GAMMA_XMIN + t) : psi_(k-1)(LN_GAMMA_XMIN) / k! for k = 0, 1, ...
+const LN_GAMMA_MIN: [f64; 22] = [
+    -0.12148629053584961, -9.2412655217294275e-17,
+    0.48383612272381063, -0.14758772299453073,
+    0.064624940238912768, -0.032788541088481316,
+    0.017970675115210401, -0.010314223036636392,
+    0.0061005360205178916, -0.0036845696083163754,
+    0.0022597648232218119, -0.0014022514459044518,
+    8.7823263471768197e-4, -5.5419495279668264e-4,
+    3.51912956837847e-4, -2.2465344369595543e-4,
+    1.4407039542093314e-4, -9.2760986554717589e-5,
+    5.9934733439794325e-5, -3.8845838894526634e-5,
+    2.5247663291730135e-5, -1.6450858338395627e-5
+];
+
+/// Stirling series coefficients : B_{2k} / (2k (2k - 1))
+const STIRLING: [f64; 8] = [
+    1f64 / 12f64, -1f64 / 360f64, 1f64 / 1260f64, -1f64 / 1680f64,
+    1f64 / 1188f64, -691f64 / 360360f64, 1f64 / 156f64, -3617f64 / 122400f64
+];
+
+/// ln(sqrt(2 pi))
+const LN_SQRT_2PI: f64 = 0.91893853320467274178;
+
+/// sqrt(2 pi)
+const SQRT_2PI: f64 = 2.5066282746310005024;
+
+/// 1 - Euler-Mascheroni constant
+const ONE_MINUS_EULER: f64 = 0.42278433509846713939;
+
+/// Euler-Mascheroni constant
+const EULER: f64 = 0.57721566490153286061;
+
+/// Above this, Gamma(z) overflows
+const GAMMA_MAX: f64 = 171.61447887182298;
+
+/// ln(f64::MAX)
+const LN_MAX: f64 = 709.78271289338397;
+/// ln(2) = LN2_HI + LN2_LO, with q LN2_HI exact for |q| < 2^20
+const LN2_HI: f64 = 6.93147180369123816490e-1;
+const LN2_LO: f64 = 1.90821492927058770002e-10;
+
+/// Threshold of Stirling series
+const STIRLING_MIN: f64 = 10f64;
+
+/// Logarithm Gamma
+///
+/// Only valid for `z > 0`. Use `ln_gamma_signed` for negative arguments.
+///
+/// * `z >= 10` : Stirling series
+/// * `1.25 <= z < 1.5` : Taylor series around the minimum of Gamma, where `ln Gamma(z + 1) - ln(z)`
+///   would cancel
+/// * `0.5 <= z < 10` : Taylor series of `ln Gamma(2 + e)` with `|e| <= 0.5` after recurrence,
+///   which keeps full relative accuracy near the zeros at `z = 1` and `z = 2`
+/// * `z < 0.5` : `ln Gamma(z) = ln Gamma(z + 1) - ln(z)`
+///
+/// Max error measured against 50-digit reference values is 3 ULP for `z` in `(0, 1e300)`.
+pub fn ln_gamma(z: f64) -> f64 {
+    if z.is_nan() || z < 0f64 {
+        f64::NAN
+    } else if z == 0f64 || z == f64::INFINITY {
+        f64::INFINITY
+    } else if z >= STIRLING_MIN {
+        stirling_ln_gamma(z)
+    } else if z < 0.5 {
+        ln_gamma_2p(z) - z.ln_1p() - z.ln()
+    } else if z < 1.25 {
+        ln_gamma_2p(z - 1f64) - (z - 1f64).ln_1p()
+    } else if z < 1.5 {
+        // z - LN_GAMMA_XMIN is exact here
+        let t = z - LN_GAMMA_XMIN;
+        LN_GAMMA_MIN.iter().rev().fold(0f64, |acc, &c| acc * t + c)
     } else {
-        1f64 - bt * betacf(b, a, 1f64 - x) / b
+        // Gamma(z) = (z - 1) (z - 2) ... (z - n) Gamma(z - n)
+        let mut z = z;
+        let mut prod = 1f64;
+        while z >= 2.5 {
+            z -= 1f64;
+            prod *= z;
+        }
+        ln_gamma_2p(z - 2f64) + prod.ln()
     }
 }
 
-/// Continued fraction beta
-fn betacf(a: f64, b: f64, x: f64) -> f64 {
-    let qab = a + b;
-    let qap = a + 1f64;
-    let qam = a - 1f64;
-    let mut c = 1f64;
-    let mut d = 1f64 - qab * x / qap;
-    if d.abs() < FPMIN {
-        d = FPMIN;
+/// `ln Gamma(2 + e)` for `|e| <= 0.5`
+fn ln_gamma_2p(e: f64) -> f64 {
+    let s = LN_GAMMA_2P.iter().rev().fold(0f64, |acc, &c| acc * e + c);
+    e * (ONE_MINUS_EULER + e * s)
+}
+
+/// Stirling series of `ln Gamma(z)` without the leading terms
+fn stirling_correction(z: f64) -> f64 {
+    let z2 = 1f64 / (z * z);
+    STIRLING.iter().rev().fold(0f64, |acc, &c| acc * z2 + c) / z
+}
+
+/// `ln Gamma(z)` by Stirling series (for large `z`)
+fn stirling_ln_gamma(z: f64) -> f64 {
+    (z - 0.5) * z.ln() - z + LN_SQRT_2PI + stirling_correction(z)
+}
+
+/// Gamma function
+///
+/// * integer `z` : exact `(z - 1)!` from the factorial table, `+inf` beyond `171`
+/// * `z >= 10` : Stirling series (the power is split to avoid spurious overflow)
+/// * `0.5 <= z < 10` : recurrence to `[1.5, 2.5)` and Taylor series of `ln Gamma(2 + e)`
+/// * `z < 0.5` : reflection formula `Gamma(z) Gamma(1-z) = pi / sin(pi z)`
+///
+/// Max error measured against 50-digit reference values is 6 ULP for `z` in `(-170, 171.6)`.
+pub fn gamma(z: f64) -> f64 {
+    if z >= 1f64 && z == z.floor() {
+        // Gamma(n) = (n - 1)! is exact up to the overflow threshold
+        return if z > GAMMA_MAX {
+            f64::INFINITY
+        } else {
+            FACTORIAL[z as usize - 1]
+        };
     }
-    d = 1f64 / d;
-    let mut h = d;
-    for m in 1 .. 10000 {
-        let m = m as f64;
-        let m2 = 2f64 * m;
-        let mut aa = m * (b - m) * x / ((qam + m2) * (a + m2));
-        d = 1f64 + aa * d;
-        if d.abs() < FPMIN {
-            d = FPMIN;
+
+    if z < 0f64 {
+        // Gamma(1-z) = -z Gamma(-z) avoids the rounding of 1 - z
+        PI / (sin_pi(z) * -z * gamma(-z))
+    } else if z < 0.5 {
+        PI / (sin_pi(z) * gamma(1f64 - z))
+    } else if z > GAMMA_MAX {
+        f64::INFINITY
+    } else if z >= STIRLING_MIN {
+        // Gamma(z) = sqrt(2 pi) z^(z - 1/2) e^(-z) e^(correction)
+        let p = z.powf(0.5 * z - 0.25);
+        SQRT_2PI * stirling_correction(z).exp() * (p * (-z).exp() * p)
+    } else {
+        let mut z = z;
+        let mut prod = 1f64;
+        while z >= 2.5 {
+            z -= 1f64;
+            prod *= z;
         }
-        c = 1f64 + aa / c;
-        if c.abs() < FPMIN {
-            c = FPMIN;
+        while z < 1.5 {
+            prod /= z;
+            z += 1f64;
         }
-        d = 1f64 / d;
-        h *= d * c;
-        aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
-        d = 1f64 + aa * d;
-        if d.abs() < FPMIN {
-            d = FPMIN;
+        ln_gamma_2p(z - 2f64).exp() * prod
+    }
+}
+
+/// Signed logarithm Gamma
+///
+/// Returns `(ln|Gamma(z)|, sign of Gamma(z))` for every real `z` (like C `lgamma_r`).
+/// Negative arguments use the reflection formula `Gamma(z) Gamma(1-z) = pi / sin(pi z)`.
+/// At the poles `z = 0, -1, -2, ...` the result is `(+inf, 1)`.
+pub fn ln_gamma_signed(z: f64) -> (f64, i8) {
+    if z.is_nan() {
+        (f64::NAN, 1)
+    } else if z > 0f64 {
+        (ln_gamma(z), 1)
+    } else if z == z.floor() {
+        (f64::INFINITY, 1)
+    } else {
+        let s = sin_pi(z);
+        let sign = if s < 0f64 { -1 } else { 1 };
+        // Gamma(1-z) = -z Gamma(-z) avoids the rounding of 1 - z
+        ((PI / (s.abs() * -z)).ln() - ln_gamma(-z), sign)
+    }
+}
+
+// =============================================================================
+// Polygamma functions
+// =============================================================================
+/// Bernoulli numbers divided by factorial : B_{2k} / (2k)!
+const BERNOULLI_FAC: [f64; 15] = [
+    0.083333333333333333, -0.0013888888888888889,
+    3.3068783068783069e-5, -8.2671957671957672e-7,
+    2.0876756987868099e-8, -5.2841901386874932e-10,
+    1.3382536530684679e-11, -3.3896802963225829e-13,
+    8.5860620562778446e-15, -2.1748686985580619e-16,
+    5.5090028283602295e-18, -1.3954464685812523e-19,
+    3.5347070396294675e-21, -8.9535174270375469e-23,
+    2.2679524523376831e-24
+];
+
+/// Asymptotic coefficients of digamma : B_{2k} / 2k
+const DIGAMMA_ASYM: [f64; 8] = [
+    1f64 / 12f64, -1f64 / 120f64, 1f64 / 252f64, -1f64 / 240f64,
+    1f64 / 132f64, -691f64 / 32760f64, 1f64 / 12f64, -3617f64 / 8160f64
+];
+
+/// Positive root of digamma `x0 = DIGAMMA_ROOT_HI + DIGAMMA_ROOT_LO`, where `DIGAMMA_ROOT_HI` has 31 bits
+const DIGAMMA_ROOT_HI: f64 = 1.4616321446374059;
+const DIGAMMA_ROOT_LO: f64 = 3.309564689176888e-10;
+
+/// Rational approximation on `[1, 2]` (Boost) : `psi(x) = (x - x0) (DIGAMMA_Y + P(x - 1) / Q(x - 1))`
+const DIGAMMA_Y: f64 = 0.99558162689208984;
+const DIGAMMA_P: [f64; 6] = [
+    0.25479851061131551, -0.32555031186804491,
+    -0.65031853770896507, -0.28919126444774784,
+    -0.045251321448739056, -0.0020713321167745952
+];
+const DIGAMMA_Q: [f64; 7] = [
+    1f64, 2.0767117023730469,
+    1.4606242909763515, 0.43593529692665969,
+    0.054151797245674225, 0.0021284987017821144,
+    -0.55789841321675513e-6
+];
+
+/// Digamma function
+///
+/// `psi(x) = d/dx ln Gamma(x)`.
+/// Uses the reflection formula for negative `x` and returns `NaN` at the poles `x = 0, -1, -2, ...`.
+pub fn digamma(x: f64) -> f64 {
+    if x.is_nan() || x == f64::NEG_INFINITY {
+        return f64::NAN;
+    } else if x <= 0f64 {
+        if x == x.floor() {
+            return f64::NAN;
         }
-        c = 1f64 + aa / c;
-        if c.abs() < FPMIN {
-            c = FPMIN;
+        // psi(x) = psi(1 - x) - pi * cot(pi * x)
+        return digamma(1f64 - x) - PI * cot_pi(x);
+    }
+
+    let mut x = x;
+    let mut result = 0f64;
+    if x < 10f64 {
+        // Recurrence onto [1, 2] : psi(x) = psi(x + 1) - 1 / x
+        if x < 1f64 {
+            result = -1f64 / x;
+            x += 1f64;
         }
-        d = 1f64 / d;
-        let del = d * c;
-        h *= del;
-        if (del - 1f64).abs() <= EPS {
-            break;
+        while x > 2f64 {
+            x -= 1f64;
+            result += 1f64 / x;
         }
+        // Centred on the root, so that psi keeps its relative accuracy near x0
+        let g = (x - DIGAMMA_ROOT_HI) - DIGAMMA_ROOT_LO;
+        let t = x - 1f64;
+        return result + g * DIGAMMA_Y + g * (poly(&DIGAMMA_P, t) / poly(&DIGAMMA_Q, t));
     }
-    h
+
+    // Asymptotic series
+    let x2 = 1f64 / (x * x);
+    let mut t = x2;
+    let mut s = 0f64;
+    for &c in DIGAMMA_ASYM.iter() {
+        s += c * t;
+        t *= x2;
+    }
+    result + x.ln() - 0.5 / x - s
 }
 
-/// Incomplete beta by Gauss Legendre quadrature
-fn betaiapprox(a: f64, b: f64, x: f64) -> f64 {
-    let a1 = a - 1f64;
-    let b1 = b - 1f64;
-    let mu = a / (a + b);
-    let lnmu = mu.ln();
-    let lnmuc = (1f64 - mu).ln();
-    let mut t = (a * b / ((a + b).powi(2) * (a + b + 1f64))).sqrt();
-    let xu = if x > a / (a + b) {
-        if x >= 1f64 { return 1f64; }
-        1f64.min((mu + 10f64 * t).max(x + 5f64 * t))
-    } else {
-        if x <= 0f64 { return 0f64; }
-        0f64.max((mu - 10f64 * t).min(x - 5f64 * t))
-    };
-    let mut sum = 0f64;
-    for j in 0 .. 18 {
-        t = x + (xu - x) * Y[j];
-        sum += W[j] * (a1 * (t.ln() - lnmu) + b1 * (1f64 - t).ln() - lnmuc).exp();
+/// `psi(x + delta) - psi(x)` for `x > 0` and `x + delta > 0` without cancellation for small `delta`
+fn digamma_delta(x: f64, delta: f64) -> f64 {
+    // psi(x + delta) - psi(x) = psi(x + n + delta) - psi(x + n) + sum_k delta / ((x + k) (x + k + delta))
+    let mut x = x;
+    let mut result = 0f64;
+    while x < 10f64 || x + delta < 10f64 {
+        result += delta / (x * (x + delta));
+        x += 1f64;
     }
-    let ans = sum * (xu - x) * (a1 * lnmu - ln_gamma(a) + b1 * lnmuc - ln_gamma(b) + ln_gamma(a + b)).exp();
-    if ans > 0f64 {
-        1f64 - ans
-    } else {
-        -ans
+    // Asymptotic series with 1/(2x) - 1/(2(x + delta)) and the first Bernoulli term taken exactly
+    let y = x + delta;
+    let mut s = 0.5 * delta / (x * y) + DIGAMMA_ASYM[0] * delta * (x + y) / (x * x * y * y);
+    let (x2, y2) = (1f64 / (x * x), 1f64 / (y * y));
+    let (mut tx, mut ty) = (x2, y2);
+    for &c in DIGAMMA_ASYM.iter().skip(1) {
+        tx *= x2;
+        ty *= y2;
+        s += c * (tx - ty);
     }
+    result + (delta / x).ln_1p() + s
 }
 
-pub fn invbetai(p: f64, a: f64, b: f64) -> f64 {
-    let a1 = a - 1f64;
-    let b1 = b - 1f64;
-    let mut t: f64;
-    let mut x: f64;
-    let mut u: f64;
-    if p <= 0f64 { 
-        return 0f64;
-    } else if p >= 1f64 {
-        return 1f64;
-    } else if a >= 1f64 && b >= 1f64 {
-        let pp = if p < 0.5 { p } else { 1f64 - p };
-        t = (-2f64 * pp.ln()).sqrt();
-        x = (2.30753 + t * 0.27061) / (1f64 + t * (0.99229 + t * 0.04481)) - t;
-        if p < 0.5 { x = -x; }
+/// `ln(x) - psi(x)` for `x > 0` without cancellation for large `x`
+fn ln_minus_digamma(x: f64) -> f64 {
+    if x < 10f64 {
+        return x.ln() - digamma(x);
+    }
+    let x2 = 1f64 / (x * x);
+    let mut t = x2;
+    let mut s = 0f64;
+    for &c in DIGAMMA_ASYM.iter() {
+        s += c * t;
+        t *= x2;
+    }
+    0.5 / x + s
+}
+
+/// Trigamma function
+///
+/// `psi_1(x) = d^2/dx^2 ln Gamma(x)`.
+/// Uses the reflection formula for negative `x` and returns `+inf` at the poles `x = 0, -1, -2, ...`.
+pub fn trigamma(x: f64) -> f64 {
+    polygamma(1, x)
+}
+
+/// Polygamma function of order `n`
+///
+/// `psi_n(x) = d^(n+1)/dx^(n+1) ln Gamma(x)`, so `polygamma(0, x) == digamma(x)`.
+/// For `n >= 1` this is evaluated as `(-1)^(n+1) n! zeta(n + 1, x)` with the Euler-Maclaurin
+/// summation of the Hurwitz zeta function, and the reflection formula for negative `x`.
+/// At the poles `x = 0, -1, -2, ...` the result is `+inf` for odd `n` and `NaN` for even `n`.
+pub fn polygamma(n: usize, x: f64) -> f64 {
+    if n == 0 {
+        return digamma(x);
+    } else if x.is_nan() || x == f64::NEG_INFINITY {
+        return f64::NAN;
+    } else if x <= 0f64 {
+        if x == x.floor() {
+            return if n % 2 == 1 { f64::INFINITY } else { f64::NAN };
+        }
+        // psi_n(x) = (-1)^n psi_n(1 - x) - pi^(n+1) P_n(cot(pi x))
+        // where d^n/dx^n cot(pi x) = pi^n P_n(cot(pi x))
+        let refl = PI.powi(n as i32 + 1) * cot_pi_derivative(n, cot_pi(x));
+        let psi = polygamma(n, 1f64 - x);
+        return if n % 2 == 1 { -psi - refl } else { psi - refl };
+    }
+
+    let z = factorial_f64(n) * hurwitz_zeta(n as f64 + 1f64, x);
+    if n % 2 == 1 { z } else { -z }
+}
+
+/// Hurwitz zeta function `zeta(s, a)` for `s > 1` and `a > 0` (Euler-Maclaurin summation)
+fn hurwitz_zeta(s: f64, a: f64) -> f64 {
+    let threshold = 10f64.max(s);
+    let mut a = a;
+    let mut sum = 0f64;
+    while a < threshold {
+        sum += a.powf(-s);
+        a += 1f64;
+    }
+
+    let a2 = 1f64 / (a * a);
+    let mut t = s * a.powf(-s - 1f64);
+    let mut tail = 0f64;
+    for (j, &c) in BERNOULLI_FAC.iter().enumerate() {
+        let term = c * t;
+        tail += term;
+        if term.abs() < tail.abs() * EPS {
+            break;
+        }
+        let k = 2f64 * j as f64;
+        t *= (s + k + 1f64) * (s + k + 2f64) * a2;
+    }
+    sum + a.powf(1f64 - s) / (s - 1f64) + 0.5 * a.powf(-s) + tail
+}
+
+/// Polynomial `P_n(c)` such that `d^n/dx^n cot(pi x) = pi^n P_n(cot(pi x))`
+///
+/// `P_0(c) = c` and `P_(n+1)(c) = -(1 + c^2) P_n'(c)`.
+fn cot_pi_derivative(n: usize, c: f64) -> f64 {
+    let mut coef = vec![0f64; n + 2];
+    coef[1] = 1f64;
+    for k in 0 .. n {
+        // Degree of P_k is k + 1
+        let mut next = vec![0f64; n + 2];
+        for i in 1 ..= k + 1 {
+            let d = i as f64 * coef[i];
+            next[i - 1] -= d;
+            next[i + 1] -= d;
+        }
+        coef = next;
+    }
+    coef.iter().rev().fold(0f64, |acc, &a| acc * c + a)
+}
+
+/// `sin(pi x)` with exact argument reduction
+fn sin_pi(x: f64) -> f64 {
+    // r in [-1, 1] and sin(pi x) = sin(pi r)
+    let r = x - 2f64 * (0.5 * x).round();
+    if r.abs() <= 0.5 {
+        (PI * r).sin()
+    } else {
+        r.signum() * (PI * (1f64 - r.abs())).sin()
+    }
+}
+
+/// `cos(pi x)` with exact argument reduction
+fn cos_pi(x: f64) -> f64 {
+    // |r| in [0, 1] and cos(pi x) = sin(pi (1/2 - |r|))
+    let r = x - 2f64 * (0.5 * x).round();
+    sin_pi(0.5 - r.abs())
+}
+
+/// `cot(pi x)` with exact argument reduction
+fn cot_pi(x: f64) -> f64 {
+    let r = x - x.round();
+    if r.abs() <= 0.25 {
+        1f64 / (PI * r).tan()
+    } else {
+        // cot(pi r) = tan(pi (1/2 - r)), and 1/2 - |r| is exact here
+        r.signum() * (PI * (0.5 - r.abs())).tan()
+    }
+}
+
+// =============================================================================
+// Gamma ratio
+// =============================================================================
+/// Ratio of Gamma functions `Gamma(a) / Gamma(b)`
+///
+/// Within a factor of two of each other, or beyond the range of `gamma`, this is evaluated as
+/// `gamma_delta_ratio(b, a - b)`, so the result stays accurate even when `a` and `b` are large and close together.
+/// If only `b` is a pole of Gamma the result is `0`, if only `a` is a pole the result is `NaN`,
+/// and if both are poles the limit `(-1)^(a-b) Gamma(1 - b) / Gamma(1 - a)` is returned.
+pub fn gamma_ratio(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        return f64::NAN;
+    } else if a == b {
+        return 1f64;
+    }
+    match (is_gamma_pole(a), is_gamma_pole(b)) {
+        (true, true) => {
+            let sign = if (a - b) % 2f64 == 0f64 { 1f64 } else { -1f64 };
+            return sign * gamma_ratio(1f64 - b, 1f64 - a);
+        }
+        (true, false) => return f64::NAN,
+        (false, true) => return 0f64,
+        (false, false) => (),
+    }
+
+    if b > 0f64 && 0.5 * b <= a && a <= 2f64 * b {
+        // a - b is exact
+        gamma_delta_ratio(b, a - b)
+    } else if a.abs() < GAMMA_MAX - 1f64 && b.abs() < GAMMA_MAX - 1f64 {
+        gamma(a) / gamma(b)
+    } else if a >= STIRLING_MIN && b >= STIRLING_MIN {
+        gamma_delta_ratio(b, a - b)
+    } else {
+        let (l1, s1) = ln_gamma_signed(a);
+        let (l2, s2) = ln_gamma_signed(b);
+        (s1 * s2) as f64 * (l1 - l2).exp()
+    }
+}
+
+/// Ratio of Gamma functions `Gamma(a + delta) / Gamma(a)`
+///
+/// For `a, a + delta >= 10` the Stirling series of both Gamma functions are subtracted analytically :
+///
+/// `Gamma(a + delta) / Gamma(a) = a^delta exp((a + delta - 1/2) ln(1 + delta/a) - delta + correction)`
+///
+/// which never forms `a + delta` in the dominant terms and stays accurate for arguments up to `1e15`.
+/// Smaller positive arguments are shifted above `10` by recurrence first.
+/// Poles are handled as in `pochhammer`.
+pub fn gamma_delta_ratio(a: f64, delta: f64) -> f64 {
+    if a.is_nan() || delta.is_nan() {
+        return f64::NAN;
+    } else if delta == 0f64 {
+        return 1f64;
+    }
+    let ad = a + delta;
+    if is_gamma_pole(a) {
+        return if is_gamma_pole(ad) {
+            // Gamma(a + delta) / Gamma(a) = (-1)^delta Gamma(1 - a) / Gamma(1 - a - delta)
+            let sign = if delta % 2f64 == 0f64 { 1f64 } else { -1f64 };
+            sign * gamma_delta_ratio(1f64 - ad, delta)
+        } else {
+            0f64
+        };
+    } else if is_gamma_pole(ad) {
+        return f64::NAN;
+    }
+
+    if a >= STIRLING_MIN && ad >= STIRLING_MIN {
+        let t = stirling_delta_exponent(a, delta);
+        let u = delta * a.ln();
+        if u.abs() < 2f64 * LN_MAX && t.abs() < LN_MAX {
+            // a^delta is split to avoid spurious overflow
+            let p = a.powf(0.5 * delta);
+            p * t.exp() * p
+        } else {
+            (u + t).exp()
+        }
+    } else if a > 0f64 && ad > 0f64 {
+        // Gamma(a + delta) / Gamma(a) = Gamma(a + n + delta) / Gamma(a + n) prod_(i<n) (a + i) / (a + delta + i)
+        let n = (STIRLING_MIN - a.min(ad)).ceil();
+        let prod = (0 .. n as usize).fold(1f64, |p, i| p * (a + i as f64) / (ad + i as f64));
+        prod * gamma_delta_ratio(a + n, delta)
+    } else if a.abs() < GAMMA_MAX - 1f64 && ad.abs() < GAMMA_MAX - 1f64 {
+        gamma(ad) / gamma(a)
+    } else {
+        let (l1, s1) = ln_gamma_signed(ad);
+        let (l2, s2) = ln_gamma_signed(a);
+        (s1 * s2) as f64 * (l1 - l2).exp()
+    }
+}
+
+/// `ln(Gamma(a + delta) / Gamma(a)) - delta ln(a)` by Stirling series (`a, a + delta >= 10`)
+fn stirling_delta_exponent(a: f64, delta: f64) -> f64 {
+    let ad = a + delta;
+    (ad - 0.5) * (delta / a).ln_1p() - delta + stirling_correction(ad) - stirling_correction(a)
+}
+
+/// Poles of Gamma function : `0, -1, -2, ...`
+fn is_gamma_pole(z: f64) -> bool {
+    z <= 0f64 && z == z.floor()
+}
+
+// =============================================================================
+// Pochhammer symbols and Binomial coefficients
+// =============================================================================
+/// Largest integer order evaluated by direct product
+const POCHHAMMER_PRODUCT_MAX: f64 = 170f64;
+
+/// Pochhammer symbol
+///
+/// `(x)_a = Gamma(x + a) / Gamma(x)` for real `x` and `a`.
+/// Non-positive integer `x` is handled as the limit (e.g. `(-3)_2 = 6`, `(-3)_5 = 0`),
+/// and `NaN` is returned when only `x + a` is a pole of Gamma.
+pub fn pochhammer(x: f64, a: f64) -> f64 {
+    if x.is_nan() || a.is_nan() {
+        return f64::NAN;
+    }
+    // The product is the correct limit unless only x + a is a pole
+    let product = is_gamma_pole(x) || !is_gamma_pole(x + a);
+    if product && a == a.floor() && a.abs() <= POCHHAMMER_PRODUCT_MAX {
+        let n = a.abs() as usize;
+        if a >= 0f64 {
+            (0 .. n).fold(1f64, |p, i| p * (x + i as f64))
+        } else {
+            1f64 / (1 ..= n).fold(1f64, |p, i| p * (x - i as f64))
+        }
+    } else {
+        gamma_delta_ratio(x, a)
+    }
+}
+
+/// Rising factorial `x (x + 1) ... (x + n - 1)`
+pub fn rising_factorial(x: f64, n: usize) -> f64 {
+    pochhammer(x, n as f64)
+}
+
+/// Falling factorial `x (x - 1) ... (x - n + 1)`
+pub fn falling_factorial(x: f64, n: usize) -> f64 {
+    // x (x - 1) ... (x - n + 1) = (-1)^n (-x)_n
+    let p = pochhammer(-x, n as f64);
+    if n % 2 == 1 { -p } else { p }
+}
+
+/// `ln (x)_a` for `x > 0` and `x + a > 0`
+fn ln_pochhammer(x: f64, a: f64) -> f64 {
+    let xa = x + a;
+    if x >= STIRLING_MIN && xa >= STIRLING_MIN {
+        a * x.ln() + stirling_delta_exponent(x, a)
+    } else {
+        ln_gamma(xa) - ln_gamma(x)
+    }
+}
+
+/// Binomial coefficient `C(n, k)` for integers
+///
+/// Exact whenever `C(n, k)` fits in `u128`, otherwise within 1 ULP by `binomial_product`.
+pub fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0f64;
+    }
+    let k = k.min(n - k);
+    let mut c = 1u128;
+    for i in 0 .. k {
+        // C(n, i + 1) = C(n, i) (n - i) / (i + 1) is exact
+        match c.checked_mul((n - i) as u128) {
+            Some(t) => c = t / (i + 1) as u128,
+            None => return binomial_product(n, k),
+        }
+    }
+    c as f64
+}
+
+/// `C(n, k)` for `k <= n / 2` by `C(m, i) = C(m - 1, i - 1) m / i` with `m = n - k + i`
+///
+/// Each step is carried in double-double arithmetic, so the `k` roundings do not accumulate.
+/// The intermediate values never decrease, and are kept below `2^512` by exact power of two scaling.
+fn binomial_product(n: usize, k: usize) -> f64 {
+    let scale = 2f64.powi(512);
+    let (mut hi, mut lo) = (1f64, 0f64);
+    let mut exponent = 0;
+    for i in 1 ..= k {
+        let m = (n - k + i) as f64;
+        let d = i as f64;
+        // (hi + lo) m = p + e
+        let p = hi * m;
+        let e = hi.mul_add(m, -p) + lo * m;
+        // (p + e) / d = q + r / d, where p - q d is exact
+        let q = p / d;
+        let r = ((-q).mul_add(d, p) + e) / d;
+        hi = q + r;
+        lo = r - (hi - q);
+        if hi > scale {
+            hi /= scale;
+            lo /= scale;
+            exponent += 1;
+            if exponent > 1 {
+                // C(n, k) >= 2^1024
+                return f64::INFINITY;
+            }
+        }
+    }
+    (hi + lo) * scale.powi(exponent)
+}
+
+/// Binomial coefficient `C(n, k) = Gamma(n + 1) / (Gamma(k + 1) Gamma(n - k + 1))` for real arguments
+///
+/// * Integer `k` : `C(n, k) = (n - k + 1)_k / k!`, which is `0` for `k < 0`,
+///   and `C(n, k) = (-1)^k C(k - n - 1, k)` for negative `n`
+/// * Integer `n` and `k` : `binomial(n, k)`
+/// * Non-integer `k` : `NaN` if `n` is a negative integer (pole of the numerator)
+pub fn binomial_real(n: f64, k: f64) -> f64 {
+    if n.is_nan() || k.is_nan() {
+        return f64::NAN;
+    }
+    let n_int = n == n.floor();
+    let mut k = k;
+    if k == k.floor() {
+        if k < 0f64 || (n_int && n >= 0f64 && k > n) {
+            return 0f64;
+        }
+        if n < 0f64 {
+            // C(n, k) = (-1)^k C(k - n - 1, k)
+            let c = binomial_real(k - n - 1f64, k);
+            return if k % 2f64 == 0f64 { c } else { -c };
+        }
+        if n_int && n < 2f64.powi(53) {
+            return binomial(n as usize, k as usize);
+        }
+        if n_int && k > 0.5 * n {
+            k = n - k;
+        }
+        if k <= POCHHAMMER_PRODUCT_MAX {
+            let mut c = 1f64;
+            for i in 0 .. k as usize {
+                c = c * (n - i as f64) / (i + 1) as f64;
+            }
+            return c;
+        }
+        if !n_int && n < k - 1f64 {
+            // Reflection keeps the distance of n - k + 1 to the pole of Gamma exact
+            // C(n, k) = (-1)^(k+1) sin(pi n) Gamma(n + 1) / (pi (k - n)_(n+1))
+            let c = sin_pi(n) * gamma(n + 1f64) / (PI * pochhammer(k - n, n + 1f64));
+            if c.is_finite() && c != 0f64 {
+                return if k % 2f64 == 0f64 { -c } else { c };
+            }
+        }
+    } else if n_int && n < 0f64 {
+        return f64::NAN;
+    }
+
+    let x = n - k + 1f64;
+    let p = pochhammer(x, k);
+    let g = gamma(k + 1f64);
+    if p.is_finite() && g.is_finite() && p != 0f64 && g != 0f64 {
+        p / g
+    } else if g.is_finite() && x >= STIRLING_MIN && k >= 0f64 {
+        // (x)_k = x^k exp(t) overflows before C(n, k) does
+        let t = stirling_delta_exponent(x, k);
+        let p = x.powf(0.5 * k);
+        p * (t.exp() / g) * p
+    } else if k > -1f64 && n - k > -1f64 {
+        // C(n, k) = 1 / ((n + 1) B(n - k + 1, k + 1))
+        1f64 / ((n + 1f64) * beta(n - k + 1f64, k + 1f64))
+    } else {
+        let (l, s) = ln_binomial_signed(n, k);
+        s as f64 * l.exp()
+    }
+}
+
+/// Logarithm of the absolute value of binomial coefficient `ln |C(n, k)|`
+pub fn ln_binomial(n: f64, k: f64) -> f64 {
+    let c = binomial_real(n, k);
+    if c.is_finite() && c != 0f64 {
+        c.abs().ln()
+    } else if c == 0f64 {
+        f64::NEG_INFINITY
+    } else {
+        ln_binomial_signed(n, k).0
+    }
+}
+
+/// `(ln |C(n, k)|, sign of C(n, k))` from log gamma functions
+fn ln_binomial_signed(n: f64, k: f64) -> (f64, i8) {
+    if k > -1f64 && n - k > -1f64 {
+        (ln_pochhammer(n - k + 1f64, k) - ln_gamma(k + 1f64), 1)
+    } else {
+        let (l1, s1) = ln_gamma_signed(n + 1f64);
+        let (l2, s2) = ln_gamma_signed(k + 1f64);
+        let (l3, s3) = ln_gamma_signed(n - k + 1f64);
+        (l1 - l2 - l3, s1 * s2 * s3)
+    }
+}
+
+// =============================================================================
+// Beta function
+// =============================================================================
+/// Beta function
+///
+/// `B(z, w) = Gamma(s) / (Gamma(l + s) / Gamma(l))` with `s = min(z, w)` and `l = max(z, w)`.
+/// The ratio is expanded by Stirling series for large `l`, so large and close arguments do not cancel.
+pub fn beta(z: f64, w: f64) -> f64 {
+    let (s, l) = if z < w { (z, w) } else { (w, z) };
+    if l < STIRLING_MIN {
+        gamma(s) * gamma(l) / gamma(s + l)
+    } else if s >= GAMMA_MAX - 1f64 {
+        // B(s, l) = sqrt(2 pi (s + l) / (s l)) (s / (s + l))^s (l / (s + l))^l exp(correction)
+        let sl = s + l;
+        let c = stirling_correction(s) + stirling_correction(l) - stirling_correction(sl);
+        SQRT_2PI * (sl / (s * l)).sqrt() * (c - s * (l / s).ln_1p() - l * (s / l).ln_1p()).exp()
+    } else {
+        // Gamma(l + s) / Gamma(l) = l^s exp(t)
+        let t = stirling_delta_exponent(l, s);
+        let p = l.powf(-0.5 * s);
+        gamma(s) * (-t).exp() * p * p
+    }
+}
+
+/// Logarithm of beta function `ln B(z, w)`
+///
+/// Uses the same Stirling forms as `beta`, without exponentiating, so it stays finite where `beta` underflows.
+pub fn ln_beta(z: f64, w: f64) -> f64 {
+    let (s, l) = if z < w { (z, w) } else { (w, z) };
+    if l < STIRLING_MIN {
+        ln_gamma(s) + ln_gamma(l) - ln_gamma(s + l)
+    } else if s >= STIRLING_MIN {
+        let sl = s + l;
+        let c = stirling_correction(s) + stirling_correction(l) - stirling_correction(sl);
+        LN_SQRT_2PI + 0.5 * (sl / (s * l)).ln() + c - s * (l / s).ln_1p() - l * (s / l).ln_1p()
+    } else {
+        ln_gamma(s) - stirling_delta_exponent(l, s) - s * l.ln()
+    }
+}
+
+
+// =============================================================================
+// Error functions
+// =============================================================================
+/// Error function
+pub fn erf(x: f64) -> f64 {
+    if x >= 0f64 {
+        1.0 - erfccheb(x)
+    } else {
+        erfccheb(-x) - 1f64
+    }
+}
+
+/// Complementary error function
+pub fn erfc(x: f64) -> f64 {
+    if x >= 0f64 {
+        erfccheb(x)
+    } else {
+        2f64 - erfccheb(-x)
+    }
+}
+
+/// Scaled complementary error function `exp(x^2) erfc(x)`
+///
+/// Finite for all `x >= -26`, where `erfc` alone underflows for `x > 26.5`.
+pub fn erfcx(x: f64) -> f64 {
+    if x < 0f64 {
+        // exp(x^2) with x^2 = x2 + e split exactly, as in erfi
+        let x2 = x * x;
+        let e = x.mul_add(x, -x2);
+        let h = (0.5 * x2).exp();
+        2f64 * (h * (1f64 + e)) * h - erfcx(-x)
+    } else if x < ERFCX_CF {
+        erfccheb_scaled(x)
+    } else if x < ERFCX_ASYMPTOTIC {
+        erfcx_cf(x)
+    } else {
+        // erfcx(x) = 1 / (x sqrt(pi)) (1 - 1 / 2x^2 + ...), where the fraction would overflow
+        0.5 * FRAC_2_SQRT_PI / x
+    }
+}
+
+/// Logarithm of complementary error function `ln erfc(x)`, finite far into the upper tail
+pub fn ln_erfc(x: f64) -> f64 {
+    if x < 0f64 {
+        erfc(x).ln()
+    } else {
+        erfcx(x).ln() - x * x
+    }
+}
+
+/// Dawson function `F(x) = exp(-x^2) int_0^x exp(t^2) dt`
+///
+/// Chebyshev series on `[0, 1]`, `[1, 4]` and in `1 / x^2` beyond, so that `F(x) ~ 1 / (2x)` for large `|x|`.
+pub fn dawson(x: f64) -> f64 {
+    let y = x.abs();
+    if y <= 1f64 {
+        x * (0.75 + chebyshev(&DAWSON_SMALL, 2f64 * y * y - 1f64))
+    } else if y <= 4f64 {
+        chebyshev(&DAWSON_MID, (2f64 * y * y - 17f64) / 15f64) / x
+    } else {
+        (0.5 + chebyshev(&DAWSON_LARGE, 32f64 / (y * y) - 1f64)) / x
+    }
+}
+
+/// Imaginary error function `erfi(x) = -i erf(ix) = 2 / sqrt(pi) exp(x^2) F(x)`
+pub fn erfi(x: f64) -> f64 {
+    if x.is_infinite() {
+        return x;
+    }
+    // x^2 = x2 + e exactly, and exp(x2) is split so that erfi stays finite up to its own overflow
+    let x2 = x * x;
+    let e = x.mul_add(x, -x2);
+    let h = (0.5 * x2).exp();
+    FRAC_2_SQRT_PI * (h * dawson(x) * (1f64 + e)) * h
+}
+
+/// Continued fraction `exp(x^2) erfc(x) = 1 / sqrt(pi) / (x + (1/2) / (x + 1 / (x + (3/2) / (x + ...))))`
+fn erfcx_cf(x: f64) -> f64 {
+    let mut f = x;
+    let mut c = x;
+    let mut d = 0f64;
+    for n in 1 .. CF_MAX_ITER {
+        let an = 0.5 * n as f64;
+        d = x + an * d;
+        if d.abs() < FPMIN {
+            d = FPMIN;
+        }
+        c = x + an / c;
+        if c.abs() < FPMIN {
+            c = FPMIN;
+        }
+        d = 1f64 / d;
+        let del = c * d;
+        f *= del;
+        if (del - 1f64).abs() <= EPS {
+            break;
+        }
+    }
+    FRAC_2_SQRT_PI / (2f64 * f)
+}
+
+/// Chebyshev coefficients
+fn erfccheb(z: f64) -> f64 {
+    let (t, e) = erfccheb_parts(z);
+    t * (-z.powi(2) + e).exp()
+}
+
+/// Scaled complementary error function `exp(z^2) erfc(z)` for `z >= 0`
+fn erfccheb_scaled(z: f64) -> f64 {
+    let (t, e) = erfccheb_parts(z);
+    t * e.exp()
+}
+
+/// `(t, e)` with `erfc(z) = t exp(-z^2 + e)`
+fn erfccheb_parts(z: f64) -> (f64, f64) {
+    let mut d = 0f64;
+    let mut dd = 0f64;
+
+    assert!(z >= 0f64, "erfccheb requires nonnegative argument");
+    let t = 2f64 / (2f64 + z);
+    let ty = 4f64 * t - 2f64;
+    for j in (1 .. NCOEF-1).rev() {
+        let tmp = d;
+        d = ty * d - dd + COF[j];
+        dd = tmp;
+    }
+    (t, 0.5 * (COF[0] + ty * d) - dd)
+}
+
+/// Inverse of complementary error function
+///
+/// Uses `erfc^(-1)(p) = -Phi^(-1)(p / 2) / sqrt 2` with the AS241 rational approximations,
+/// returning `+inf` for `p <= 0` and `-inf` for `p >= 2`.
+pub fn inverfc(p: f64) -> f64 {
+    if p >= 2f64 {
+        return f64::NEG_INFINITY;
+    } else if p <= 0f64 {
+        return f64::INFINITY;
+    }
+    if (1f64 - p).abs() <= 0.85 {
+        inverf(1f64 - p)
+    } else if p < 1f64 {
+        FRAC_1_SQRT_2 * as241_tail(p.ln() - LN_2)
+    } else {
+        -FRAC_1_SQRT_2 * as241_tail((2f64 - p).ln() - LN_2)
+    }
+}
+
+/// Inverse of error function
+///
+/// Uses `erf^(-1)(p) = Phi^(-1)((1 + p) / 2) / sqrt 2` with the AS241 rational approximations,
+/// returning `+inf` for `p >= 1` and `-inf` for `p <= -1`.
+pub fn inverf(p: f64) -> f64 {
+    if p >= 1f64 {
+        return f64::INFINITY;
+    } else if p <= -1f64 {
+        return f64::NEG_INFINITY;
+    }
+    if p.abs() <= 0.85 {
+        // Scale after the product so subnormal p keeps its bits
+        p * as241_central(0.5 * p) * (0.5 * FRAC_1_SQRT_2)
+    } else {
+        let x = FRAC_1_SQRT_2 * as241_tail((1f64 - p.abs()).ln() - LN_2);
+        if p < 0f64 { -x } else { x }
+    }
+}
+
+// =============================================================================
+// Normal distribution
+// =============================================================================
+/// Standard normal cumulative distribution function `Phi(x)`
+pub fn norm_cdf(x: f64) -> f64 {
+    if x < 0f64 {
+        norm_tail(-x)
+    } else {
+        1f64 - norm_tail(x)
+    }
+}
+
+/// Standard normal survival function `1 - Phi(x)`
+pub fn norm_sf(x: f64) -> f64 {
+    norm_cdf(-x)
+}
+
+/// Logarithm of standard normal cumulative distribution function `ln Phi(x)`
+///
+/// The lower tail is `ln(erfcx(-x / sqrt 2) / 2) - x^2 / 2`, which stays finite far beyond the underflow of `Phi`.
+pub fn norm_logcdf(x: f64) -> f64 {
+    if x == f64::NEG_INFINITY {
+        f64::NEG_INFINITY
+    } else if x < 0f64 {
+        (0.5 * erfcx(-x * FRAC_1_SQRT_2)).ln() - 0.5 * x * x
+    } else {
+        (-norm_tail(x)).ln_1p()
+    }
+}
+
+/// Quantile of standard normal distribution `Phi^(-1)(p)` (probit)
+///
+/// Wichura's AS241 (PPND16), accurate to about `1e-16`.
+pub fn norm_ppf(p: f64) -> f64 {
+    assert!((0f64..=1f64).contains(&p), "Bad p in routine norm_ppf");
+    let q = p - 0.5;
+    if q.abs() <= 0.425 {
+        return q * as241_central(q);
+    }
+    let r = if q < 0f64 { p } else { 1f64 - p };
+    if r == 0f64 {
+        return if q < 0f64 { f64::NEG_INFINITY } else { f64::INFINITY };
+    }
+    let x = as241_tail(r.ln());
+    if q < 0f64 { -x } else { x }
+}
+
+/// AS241 central region : `Phi^(-1)(1/2 + q) / q` for `|q| <= 0.425`
+fn as241_central(q: f64) -> f64 {
+    let r = 0.180625 - q * q;
+    poly(&AS241_A, r) / poly(&AS241_B, r)
+}
+
+/// AS241 tails : `-Phi^(-1)(r)` from `ln r` for `r < 0.075`
+fn as241_tail(ln_r: f64) -> f64 {
+    let r = (-ln_r).sqrt();
+    if r <= 5f64 {
+        let r = r - 1.6;
+        poly(&AS241_C, r) / poly(&AS241_D, r)
+    } else {
+        let r = r - 5f64;
+        poly(&AS241_E, r) / poly(&AS241_F, r)
+    }
+}
+
+/// `Phi(-x)` for `x >= 0`, as `erfcx(x / sqrt 2) exp(-x^2 / 2) / 2` with `x^2` split exactly
+fn norm_tail(x: f64) -> f64 {
+    if x > NORM_TAIL_MAX {
+        return 0f64;
+    }
+    let x2 = x * x;
+    let e = x.mul_add(x, -x2);
+    0.5 * erfcx(x * FRAC_1_SQRT_2) * (-0.5 * x2).exp() * (1f64 - 0.5 * e)
+}
+
+// =============================================================================
+// Complex number
+// =============================================================================
+/// Complex number `re + i im`
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// Complex conjugate
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// Absolute value `|z|`
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Complex exponential
+    pub fn exp(self) -> Self {
+        let r = self.re.exp();
+        Complex::new(r * self.im.cos(), r * self.im.sin())
+    }
+
+    /// Multiplication by `i`
+    pub fn mul_i(self) -> Self {
+        Complex::new(-self.im, self.re)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex::new(re, 0f64)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(self.re * rhs.re - self.im * rhs.im, self.re * rhs.im + self.im * rhs.re)
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: f64) -> Complex {
+        Complex::new(self.re * rhs, self.im * rhs)
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Complex) -> Complex {
+        // Smith's algorithm
+        if rhs.re.abs() >= rhs.im.abs() {
+            let r = rhs.im / rhs.re;
+            let d = rhs.re + rhs.im * r;
+            Complex::new((self.re + self.im * r) / d, (self.im - self.re * r) / d)
+        } else {
+            let r = rhs.re / rhs.im;
+            let d = rhs.re * r + rhs.im;
+            Complex::new((self.re * r + self.im) / d, (self.im * r - self.re) / d)
+        }
+    }
+}
+
+// =============================================================================
+// Complex error functions
+// =============================================================================
+/// Faddeeva function `w(z) = exp(-z^2) erfc(-iz)`
+///
+/// Poppe & Wijers (1990), Algorithm 680 : power series near the origin and
+/// the Laplace continued fraction elsewhere, about 14 significant digits.
+/// Close to the real axis a Taylor series around `w(x)` keeps the small real part accurate.
+/// Far from the origin `w(z) = i / (sqrt(pi) z)` to double precision.
+/// The lower half plane uses `w(z) = 2 exp(-z^2) - w(-z)`.
+pub fn faddeeva(z: Complex) -> Complex {
+    let xabs = z.re.abs();
+    let yabs = z.im.abs();
+    let x = xabs / 6.3;
+    let y = yabs / 4.4;
+    let mut qrho = x * x + y * y;
+    let xquad = (xabs - yabs) * (xabs + yabs);
+    let yquad = 2f64 * xabs * yabs;
+    let series = qrho < 0.085264;
+
+    let (mut u, mut v);
+    let (mut u2, mut v2) = (0f64, 0f64);
+    if xabs.max(yabs) > FADDEEVA_ASYMPTOTIC {
+        // The next term is 1 / (2 z^2) relative to the first; z itself is never squared
+        let w = Complex::new(0f64, 0.5 * FRAC_2_SQRT_PI) / Complex::new(xabs, yabs);
+        u = w.re;
+        v = w.im;
+    } else if series {
+        // w(z) = exp(-z^2) (1 - erf(-iz)) with the Taylor series of erf
+        qrho = (1f64 - 0.85 * y) * qrho.sqrt();
+        let n = (6f64 + 72f64 * qrho).round() as usize;
+        let mut j = 2 * n + 1;
+        let mut xsum = 1f64 / j as f64;
+        let mut ysum = 0f64;
+        for i in (1 ..= n).rev() {
+            j -= 2;
+            let xaux = (xsum * xquad - ysum * yquad) / i as f64;
+            ysum = (xsum * yquad + ysum * xquad) / i as f64;
+            xsum = xaux + 1f64 / j as f64;
+        }
+        let u1 = -FRAC_2_SQRT_PI * (xsum * yabs + ysum * xabs) + 1f64;
+        let v1 = FRAC_2_SQRT_PI * (xsum * xabs - ysum * yabs);
+        let daux = (-xquad).exp();
+        u2 = daux * yquad.cos();
+        v2 = -daux * yquad.sin();
+        u = u1 * u2 - v1 * v2;
+        v = u1 * v2 + v1 * u2;
+    } else {
+        // Laplace continued fraction, accelerated by a truncated Taylor series in h near the real axis
+        let (h, kapn, nu) = if qrho > 1f64 {
+            let qrho = qrho.sqrt();
+            (0f64, 0usize, (3f64 + 1442f64 / (26f64 * qrho + 77f64)) as usize)
+        } else {
+            let qrho = (1f64 - y) * (1f64 - qrho).sqrt();
+            (1.88 * qrho, (7f64 + 34f64 * qrho).round() as usize, (16f64 + 26f64 * qrho).round() as usize)
+        };
+        let h2 = 2f64 * h;
+        let mut qlambda = if h > 0f64 { h2.powi(kapn as i32) } else { 0f64 };
+        let (mut rx, mut ry, mut sx, mut sy) = (0f64, 0f64, 0f64, 0f64);
+        for n in (0 ..= nu).rev() {
+            let np1 = (n + 1) as f64;
+            let tx = yabs + h + np1 * rx;
+            let ty = xabs - np1 * ry;
+            let c = 0.5 / (tx * tx + ty * ty);
+            rx = c * tx;
+            ry = c * ty;
+            if h > 0f64 && n <= kapn {
+                let tx = qlambda + sx;
+                sx = rx * tx - ry * sy;
+                sy = ry * tx + rx * sy;
+                qlambda /= h2;
+            }
+        }
+        if h == 0f64 {
+            u = FRAC_2_SQRT_PI * rx;
+            v = FRAC_2_SQRT_PI * ry;
+        } else {
+            u = FRAC_2_SQRT_PI * sx;
+            v = FRAC_2_SQRT_PI * sy;
+        }
+        if yabs == 0f64 {
+            u = (-xabs * xabs).exp();
+        } else if yabs < FADDEEVA_TAYLOR && xabs < 10f64 {
+            // Re w is small next to Im w near the real axis, so expand around w(x) in powers of iy
+            let (re, im) = faddeeva_taylor(xabs, yabs);
+            u = re;
+            v = im;
+        }
+    }
+
+    if z.im < 0f64 {
+        if series {
+            u2 *= 2f64;
+            v2 *= 2f64;
+        } else {
+            let w1 = 2f64 * (-xquad).exp();
+            u2 = w1 * yquad.cos();
+            v2 = -w1 * yquad.sin();
+        }
+        u = u2 - u;
+        v = v2 - v;
+        if z.re > 0f64 {
+            v = -v;
+        }
+    } else if z.re < 0f64 {
+        v = -v;
+    }
+    Complex::new(u, v)
+}
+
+/// `w(x + iy)` by Taylor series around the real axis, using `w' = -2z w + 2i / sqrt(pi)`
+fn faddeeva_taylor(x: f64, y: f64) -> (f64, f64) {
+    // c_n = w^(n)(x) y^n / n! and w(x + iy) = sum_n i^n c_n
+    let mut c0 = Complex::new((-x * x).exp(), faddeeva(Complex::new(x, 0f64)).im);
+    let mut c1 = (c0 * (-2f64 * x) + Complex::new(0f64, FRAC_2_SQRT_PI)) * y;
+    let mut sum = c0 + c1.mul_i();
+    let mut ipow = Complex::new(0f64, 1f64);
+    for n in 1 .. {
+        let c2 = (c1 * (2f64 * x * y) + c0 * (2f64 * y * y)) * (-1f64 / (n + 1) as f64);
+        ipow = ipow.mul_i();
+        let del = ipow * c2;
+        sum = sum + del;
+        if del.re.abs() <= EPS * sum.re.abs() && del.im.abs() <= EPS * sum.im.abs() {
+            break;
+        }
+        c0 = c1;
+        c1 = c2;
+    }
+    (sum.re, sum.im)
+}
+
+/// Complementary error function of complex argument, `erfc(z) = exp(-z^2) w(iz)`
+pub fn erfc_complex(z: Complex) -> Complex {
+    if z.im == 0f64 {
+        return Complex::from(erfc(z.re));
+    } else if z.re < 0f64 {
+        // w(iz) is in the lower half plane, where it grows like exp(z^2)
+        return Complex::from(2f64) - erfc_complex(-z);
+    }
+    exp_mz2_mul(z, faddeeva(z.mul_i()))
+}
+
+/// `exp(-z^2) w` without forming `z^2`
+///
+/// When `exp(-z^2)` alone overflows or underflows, the product is formed from logarithms,
+/// so an infinite result keeps the signs of its components instead of becoming `inf * 0`.
+fn exp_mz2_mul(z: Complex, w: Complex) -> Complex {
+    let re = (z.im - z.re) * (z.re + z.im);
+    let im = -2f64 * z.re * z.im;
+    if re.abs() < LN_MAX {
+        Complex::new(re, im).exp() * w
+    } else {
+        Complex::new(re + w.abs().ln(), im + w.im.atan2(w.re)).exp()
+    }
+}
+
+/// `erf(x + iy)` by Taylor series in `x` around `iy`
+///
+/// `erf(x + iy) = erfi(y) i + 2 / sqrt(pi) exp(y^2) sum_n (-i)^n h_n(y) x^(n+1) / (n+1)!`
+/// with the Hermite polynomials `H_n(iy) = i^n h_n(y)`, so the small real part is not
+/// obtained as a difference from 1.
+fn erf_near_imaginary(x: f64, y: f64) -> Complex {
+    // h_(n+1) = 2y h_n + 2n h_(n-1)
+    let (mut h0, mut h1) = (1f64, 2f64 * y);
+    let mut t = x;
+    let (mut re, mut im) = (x, 0f64);
+    for n in 1 .. CF_MAX_ITER {
+        t *= x / (n + 1) as f64;
+        let del = h1 * t;
+        // (-i)^n cycles through -i, -1, i, 1
+        match n % 4 {
+            1 => im -= del,
+            2 => re -= del,
+            3 => im += del,
+            _ => re += del,
+        }
+        if del.abs() <= EPS * re.abs().max(im.abs()) {
+            break;
+        }
+        (h0, h1) = (h1, 2f64 * y * h1 + 2f64 * n as f64 * h0);
+    }
+    // exp(y^2) with y^2 = y2 + e split exactly, as in erfi
+    let y2 = y * y;
+    let e = y.mul_add(y, -y2);
+    let h = (0.5 * y2).exp();
+    let c = FRAC_2_SQRT_PI * (1f64 + e);
+    Complex::new((h * c * re) * h, erfi(y) + (h * c * im) * h)
+}
+
+/// Error function of complex argument
+///
+/// Taylor series for `|z| < 1/2`, where `1 - erfc(z)` would cancel,
+/// and a Taylor series in `Re z` near the imaginary axis, where the real part would cancel.
+pub fn erf_complex(z: Complex) -> Complex {
+    if z.abs() < 0.5 {
+        // erf(z) = 2 / sqrt(pi) sum_n (-1)^n z^(2n+1) / (n! (2n+1))
+        let mz2 = -(z * z);
+        let mut term = z;
+        let mut sum = z;
+        for n in 1 .. {
+            term = term * mz2 * (1f64 / n as f64);
+            let del = term * (1f64 / (2 * n + 1) as f64);
+            sum = sum + del;
+            if del.abs() <= EPS * sum.abs() {
+                break;
+            }
+        }
+        sum * FRAC_2_SQRT_PI
+    } else if z.im == 0f64 {
+        Complex::from(erf(z.re))
+    } else if z.re.abs() < 0.5 && (z.re * z.im).abs() < 0.5 {
+        erf_near_imaginary(z.re, z.im)
+    } else if z.re >= 0f64 {
+        Complex::from(1f64) - erfc_complex(z)
+    } else {
+        erfc_complex(-z) - Complex::from(1f64)
+    }
+}
+
+/// Imaginary error function of complex argument, `erfi(z) = -i erf(iz)`
+pub fn erfi_complex(z: Complex) -> Complex {
+    -erf_complex(z.mul_i()).mul_i()
+}
+
+/// Voigt profile : convolution of a Gaussian (standard deviation `sigma`) and a Lorentzian (half width `gamma`)
+///
+/// `V(x) = Re w((x + i gamma) / (sigma sqrt 2)) / (sigma sqrt(2 pi))`
+pub fn voigt_profile(x: f64, sigma: f64, gamma: f64) -> f64 {
+    assert!(sigma >= 0f64 && gamma >= 0f64 && sigma + gamma > 0f64, "Bad sigma or gamma in routine voigt_profile");
+    if sigma == 0f64 {
+        return gamma / (PI * (x * x + gamma * gamma));
+    }
+    let s = sigma * SQRT_2;
+    faddeeva(Complex::new(x / s, gamma / s)).re / (sigma * SQRT_2PI)
+}
+
+// =============================================================================
+// Fresnel integrals
+// =============================================================================
+/// Fresnel integral `S(x) = int_0^x sin(pi t^2 / 2) dt`
+pub fn fresnel_s(x: f64) -> f64 {
+    fresnel(x).0
+}
+
+/// Fresnel integral `C(x) = int_0^x cos(pi t^2 / 2) dt`
+pub fn fresnel_c(x: f64) -> f64 {
+    fresnel(x).1
+}
+
+/// Fresnel integrals `(S(x), C(x))`
+///
+/// Power series for `|x| <= 1.5` and the continued fraction of `erfc` for larger `|x|`,
+/// with `pi x^2 / 2` reduced exactly so the oscillating terms keep their phase.
+pub fn fresnel(x: f64) -> (f64, f64) {
+    let ax = x.abs();
+    let (s, c) = if ax < FPMIN.sqrt() {
+        (0f64, ax)
+    } else if ax <= FRESNEL_XMIN {
+        fresnel_series(ax)
+    } else if ax < 1f64 / EPS {
+        fresnel_cf(ax)
+    } else {
+        (0.5, 0.5)
+    };
+    if x < 0f64 { (-s, -c) } else { (s, c) }
+}
+
+/// Power series `C + iS = sum_n (i pi / 2)^n x^(2n+1) / (n! (2n+1))`
+fn fresnel_series(x: f64) -> (f64, f64) {
+    let fact = FRAC_PI_2 * x * x;
+    let mut sums = 0f64;
+    let mut sumc = x;
+    let mut term = x;
+    let mut sign = 1f64;
+    for k in 1 .. CF_MAX_ITER {
+        term *= fact / k as f64;
+        let del = sign * term / (2 * k + 1) as f64;
+        if k % 2 == 1 {
+            sums += del;
+            sign = -sign;
+        } else {
+            sumc += del;
+        }
+        if term < EPS * sums.abs().min(sumc.abs()) {
+            break;
+        }
+    }
+    (sums, sumc)
+}
+
+/// Continued fraction for `erfc` with `C + iS = (1 + i)/2 (1 - exp(i pi x^2 / 2) h)`
+fn fresnel_cf(x: f64) -> (f64, f64) {
+    let pix2 = PI * x * x;
+    let mut b = Complex::new(1f64, -pix2);
+    let mut cc = Complex::from(f64::MAX * EPS);
+    let mut d = Complex::from(1f64) / b;
+    let mut h = d;
+    let mut n = -1f64;
+    for _ in 2 .. CF_MAX_ITER {
+        n += 2f64;
+        let a = -n * (n + 1f64);
+        b = b + Complex::from(4f64);
+        d = Complex::from(1f64) / (d * a + b);
+        cc = b + Complex::from(a) / cc;
+        let del = cc * d;
+        h = h * del;
+        if (del.re - 1f64).abs() + del.im.abs() <= EPS {
+            break;
+        }
+    }
+    h = h * Complex::new(x, -x);
+    // pi x^2 / 2 = pi (x2 + e) / 2 with x2 reduced modulo 4
+    let x2 = x * x;
+    let e = x.mul_add(x, -x2);
+    let r = 0.5 * (x2 % 4f64 + e);
+    let phase = Complex::new(sin_pi(0.5 - r), sin_pi(r));
+    let cs = Complex::new(0.5, 0.5) * (Complex::from(1f64) - phase * h);
+    (cs.im, cs.re)
+}
+
+// =============================================================================
+// Bessel functions
+// =============================================================================
+/// Bessel function of the first kind of order zero `J_0(x)`
+///
+/// Chebyshev series in `x^2` with the first two zeros factored out for `|x| <= 8`,
+/// Hankel's asymptotic form with Chebyshev series for `P_0`, `Q_0` beyond.
+pub fn j0(x: f64) -> f64 {
+    let x = x.abs();
+    if x <= BESSEL_CHEB_MAX {
+        bessel_zero_factor(x, &BESSEL_J0_ZEROS) * chebyshev(&BESSEL_J0_SMALL, x * x / 32f64 - 1f64)
+    } else {
+        bessel_hankel(x, &BESSEL_P0, &BESSEL_Q0).0
+    }
+}
+
+/// Bessel function of the first kind of order one `J_1(x)`
+pub fn j1(x: f64) -> f64 {
+    let ax = x.abs();
+    if ax <= BESSEL_CHEB_MAX {
+        x * bessel_zero_factor(ax, &BESSEL_J1_ZEROS) * chebyshev(&BESSEL_J1_SMALL, x * x / 32f64 - 1f64)
+    } else {
+        x.signum() * bessel_hankel(ax, &BESSEL_P1, &BESSEL_Q1).1
+    }
+}
+
+/// Bessel function of the second kind of order zero `Y_0(x)`
+///
+/// `Y_0(x) - 2 ln(x) J_0(x) / pi` is a Chebyshev series in `x^2` for `x <= 8`.
+/// Returns `NaN` for `x < 0`.
+pub fn y0(x: f64) -> f64 {
+    if x <= BESSEL_CHEB_MAX {
+        FRAC_2_PI * x.ln() * j0(x) + chebyshev(&BESSEL_Y0_SMALL, x * x / 32f64 - 1f64)
+    } else {
+        bessel_hankel(x, &BESSEL_P0, &BESSEL_Q0).1
+    }
+}
+
+/// Bessel function of the second kind of order one `Y_1(x)`
+///
+/// Returns `NaN` for `x < 0`.
+pub fn y1(x: f64) -> f64 {
+    if x <= BESSEL_CHEB_MAX {
+        FRAC_2_PI * (x.ln() * j1(x) - 1f64 / x) + x * chebyshev(&BESSEL_Y1_SMALL, x * x / 32f64 - 1f64)
+    } else {
+        -bessel_hankel(x, &BESSEL_P1, &BESSEL_Q1).0
+    }
+}
+
+/// Bessel function of the first kind of integer order `J_n(x)`
+///
+/// Forward recurrence from `J_0`, `J_1` for `|x| > n`, Miller's downward recurrence otherwise.
+pub fn jn(n: usize, x: f64) -> f64 {
+    match n {
+        0 => return j0(x),
+        1 => return j1(x),
+        _ => (),
+    }
+    let ax = x.abs();
+    let nf = n as f64;
+    let ans = if ax == 0f64 || ax == f64::INFINITY {
+        0f64
+    } else if ax > nf {
+        let tox = 2f64 / ax;
+        let mut bjm = j0(ax);
+        let mut bj = j1(ax);
+        for j in 1 .. n {
+            let bjp = j as f64 * tox * bj - bjm;
+            bjm = bj;
+            bj = bjp;
+        }
+        bj
+    } else if 0.25 * ax * ax < EPS {
+        // Leading term (x/2)^n / n!
+        (1 ..= n).fold(1f64, |acc, k| acc * (0.5 * ax) / k as f64)
+    } else {
+        // Normalized by J_0 + 2 (J_2 + J_4 + ...) = 1
+        let tox = 2f64 / ax;
+        let m = 2 * ((n + (BESSEL_MILLER_ACC * nf).sqrt() as usize) / 2);
+        let mut jsum = false;
+        let mut bjp = 0f64;
+        let mut bj = 1f64;
+        let mut sum = 0f64;
+        let mut ans = 0f64;
+        for j in (1 ..= m).rev() {
+            let bjm = j as f64 * tox * bj - bjp;
+            bjp = bj;
+            bj = bjm;
+            if bj.abs() > BESSEL_RESCALE {
+                bj /= BESSEL_RESCALE;
+                bjp /= BESSEL_RESCALE;
+                ans /= BESSEL_RESCALE;
+                sum /= BESSEL_RESCALE;
+            }
+            if jsum {
+                sum += bj;
+            }
+            jsum = !jsum;
+            if j == n {
+                ans = bjp;
+            }
+        }
+        ans / (2f64 * sum - bj)
+    };
+    if x < 0f64 && n % 2 == 1 {
+        -ans
+    } else {
+        ans
+    }
+}
+
+/// Bessel function of the second kind of integer order `Y_n(x)`
+///
+/// Forward recurrence from `Y_0`, `Y_1`. Returns `NaN` for `x < 0`.
+pub fn yn(n: usize, x: f64) -> f64 {
+    match n {
+        0 => return y0(x),
+        1 => return y1(x),
+        _ => (),
+    }
+    let tox = 2f64 / x;
+    let mut bym = y0(x);
+    let mut by = y1(x);
+    for j in 1 .. n {
+        if by.is_infinite() {
+            break;
+        }
+        let byp = j as f64 * tox * by - bym;
+        bym = by;
+        by = byp;
+    }
+    by
+}
+
+/// Bessel function of the first kind `J_nu(x)`
+///
+/// Integer orders use `jn`. Otherwise Temme's series (`x < 2`), Steed's method (`x < max(25, nu^2)`)
+/// or Hankel's asymptotic expansion, and `J_(-nu) = cos(nu pi) J_nu - sin(nu pi) Y_nu` for `nu < 0`.
+/// Returns `NaN` for `x < 0` with non-integer `nu`.
+pub fn bessel_j(nu: f64, x: f64) -> f64 {
+    if nu.is_nan() || x.is_nan() {
+        return f64::NAN;
+    }
+    if nu == nu.round() {
+        let n = nu.abs() as usize;
+        let j = jn(n, x);
+        return if nu < 0f64 && n % 2 == 1 { -j } else { j };
+    }
+    if x < 0f64 {
+        return f64::NAN;
+    }
+    if nu < 0f64 {
+        let (j, y) = bessel_jy(-nu, x);
+        cos_pi(nu) * j + sin_pi(nu) * y
+    } else {
+        bessel_jy(nu, x).0
+    }
+}
+
+/// Bessel function of the second kind `Y_nu(x)`
+///
+/// Integer orders use `yn`. Otherwise as in `bessel_j`, with `Y_(-nu) = sin(nu pi) J_nu + cos(nu pi) Y_nu`.
+/// Returns `NaN` for `x < 0`.
+pub fn bessel_y(nu: f64, x: f64) -> f64 {
+    if nu.is_nan() || x.is_nan() || x < 0f64 {
+        return f64::NAN;
+    }
+    if nu == nu.round() {
+        let n = nu.abs() as usize;
+        let y = yn(n, x);
+        return if nu < 0f64 && n % 2 == 1 { -y } else { y };
+    }
+    if nu < 0f64 {
+        let (j, y) = bessel_jy(-nu, x);
+        // cos(nu pi) = 0 at half-integer orders, where Y_nu may have overflowed
+        let c = cos_pi(nu);
+        let cy = if c == 0f64 { 0f64 } else { c * y };
+        cy - sin_pi(nu) * j
+    } else {
+        bessel_jy(nu, x).1
+    }
+}
+
+/// `prod (x - z)(x + z)` over zeros `z = hi + lo`, with full relative accuracy near each zero
+fn bessel_zero_factor(x: f64, zeros: &[(f64, f64)]) -> f64 {
+    zeros.iter().map(|&(hi, lo)| (x - hi - lo) * (x + hi)).product()
+}
+
+/// Hankel's form for `x > 8` : `(J_0, Y_0)` from `P_0`, `Q_0`, or `(-Y_1, J_1)` from `P_1`, `Q_1`
+fn bessel_hankel(x: f64, cp: &[f64], cq: &[f64]) -> (f64, f64) {
+    if x == f64::INFINITY {
+        return (0f64, 0f64);
+    }
+    let u = 128f64 / (x * x) - 1f64;
+    let s = 1f64 / (PI * x).sqrt();
+    let p = s * chebyshev(cp, u);
+    let q = s * 8f64 / x * chebyshev(cq, u);
+    // sqrt 2 cos(x - pi/4) and sqrt 2 sin(x - pi/4)
+    let (sn, cs) = x.sin_cos();
+    let (a, b) = (cs + sn, sn - cs);
+    (p * a - q * b, p * b + q * a)
+}
+
+/// `(J_nu(x), Y_nu(x))` for `nu >= 0`, `x >= 0` (Numerical Recipes `besseljy`)
+///
+/// CF1 gives `J'_nu / J_nu`, downward recurrence carries it to `mu = nu - nl` with `|mu| <= 1/2`,
+/// where Temme's series (`x < 2`) or Steed's CF2 (`x >= 2`) fixes `J_mu`, `Y_mu`.
+/// `Y` is then recurred upward to `nu`. For `(x/2)^2 < EPS (nu + 1)` only the leading term of `J_nu` is kept.
+fn bessel_jy(nu: f64, x: f64) -> (f64, f64) {
+    if x == 0f64 {
+        return (if nu == 0f64 { 1f64 } else { 0f64 }, f64::NEG_INFINITY);
+    }
+    if x >= BESSEL_ASYM_MIN.max(nu * nu) {
+        return bessel_jy_asym(nu, x);
+    }
+    let nl = if x < BESSEL_TEMME_MAX {
+        (nu + 0.5).floor()
+    } else {
+        (nu - x + 1.5).floor().max(0f64)
+    } as usize;
+    let mu = nu - nl as f64;
+    if 0.25 * x * x < EPS * (nu + 1f64) {
+        // CF1 would start from nu / x and overflow : J_nu is the leading term of its series
+        let (mut rymu, mut ry1) = bessel_y_temme(mu, x);
+        for i in 1 ..= nl {
+            if ry1.is_infinite() {
+                rymu = ry1;
+                break;
+            }
+            let rytemp = (mu + i as f64) * (2f64 / x) * ry1 - rymu;
+            rymu = ry1;
+            ry1 = rytemp;
+        }
+        return (bessel_series_prefactor(nu, x), rymu);
+    }
+    let mu2 = mu * mu;
+    let xi = 1f64 / x;
+    let xi2 = 2f64 * xi;
+    let w = xi2 / PI;
+
+    // CF1 by modified Lentz, tracking the sign of J_nu
+    let mut isign = 1f64;
+    let mut h = (nu * xi).max(FPMIN);
+    let mut d = 0f64;
+    let mut c = h;
+    for i in 1 .. CF_MAX_ITER {
+        // Not accumulated : CF1 takes about x terms for large x
+        let b = xi2 * (nu + i as f64);
+        d = b - d;
+        if d.abs() < FPMIN {
+            d = FPMIN;
+        }
+        c = b - 1f64 / c;
+        if c.abs() < FPMIN {
+            c = FPMIN;
+        }
+        d = 1f64 / d;
+        let del = c * d;
+        h *= del;
+        if d < 0f64 {
+            isign = -isign;
+        }
+        if (del - 1f64).abs() <= EPS {
+            break;
+        }
+    }
+
+    let mut rjl = isign * FPMIN;
+    let mut rjpl = h * rjl;
+    let mut rjl1 = rjl;
+    for l in 0 .. nl {
+        // Coefficients not accumulated either : nl can be large
+        let rjtemp = (nu - l as f64) * xi * rjl + rjpl;
+        rjpl = (nu - (l + 1) as f64) * xi * rjtemp - rjl;
+        rjl = rjtemp;
+        if rjl.abs() > BESSEL_RESCALE {
+            // J_nu underflows relative to J_mu
+            rjl /= BESSEL_RESCALE;
+            rjpl /= BESSEL_RESCALE;
+            rjl1 /= BESSEL_RESCALE;
+        }
+    }
+    if rjl == 0f64 {
+        rjl = EPS;
+    }
+    let f = rjpl / rjl;
+
+    let (rjmu, mut rymu, mut ry1) = if x < BESSEL_TEMME_MAX {
+        let (rymu, ry1) = bessel_y_temme(mu, x);
+        let rymup = mu * xi * rymu - ry1;
+        (w / (rymup - f * rymu), rymu, ry1)
+    } else {
+        // CF2 : p + iq = (J' + iY') / (J + iY) by complex modified Lentz
+        let mut a = 0.25 - mu2;
+        let mut b = Complex::new(2f64 * x, 2f64);
+        let mut pq = Complex::new(-0.5 * xi, 1f64);
+        let mut c = b + Complex::new(0f64, a * xi) / pq;
+        let mut d = Complex::from(1f64) / b;
+        pq = pq * c * d;
+        for i in 1 .. CF_MAX_ITER {
+            a += 2f64 * i as f64;
+            b = b + Complex::new(0f64, 2f64);
+            d = Complex::from(1f64) / (d * a + b);
+            c = b + Complex::from(a) / c;
+            let del = c * d;
+            pq = pq * del;
+            if (del.re - 1f64).abs() + del.im.abs() <= EPS {
+                break;
+            }
+        }
+        let (p, q) = (pq.re, pq.im);
+        let gam = (p - f) / q;
+        let rjmu = (w / ((p - f) * gam + q)).sqrt().copysign(rjl);
+        let rymu = rjmu * gam;
+        let rymup = rymu * (p + q / gam);
+        (rjmu, rymu, mu * xi * rymu - rymup)
+    };
+
+    let j = rjl1 * (rjmu / rjl);
+    for i in 1 ..= nl {
+        if ry1.is_infinite() {
+            rymu = ry1;
+            break;
+        }
+        let rytemp = (mu + i as f64) * xi2 * ry1 - rymu;
+        rymu = ry1;
+        ry1 = rytemp;
+    }
+    (j, rymu)
+}
+
+/// Temme's series for `(Y_mu(x), Y_(mu+1)(x))` with `|mu| <= 1/2` and `x < 2`
+fn bessel_y_temme(mu: f64, x: f64) -> (f64, f64) {
+    let mu2 = mu * mu;
+    let x2 = 0.5 * x;
+    let pimu = PI * mu;
+    let fact = if pimu.abs() < EPS { 1f64 } else { pimu / pimu.sin() };
+    // -ln(x/2), where x/2 itself underflows for the smallest subnormal x
+    let d = LN_2 - x.ln();
+    let (ch, fact2, e) = bessel_temme_exp(mu, x, d);
+    let (gam1, gam2, gampl, gammi) = bessel_temme_gamma(mu);
+    let mut ff = FRAC_2_PI * fact * (gam1 * ch + gam2 * fact2 * d);
+    let mut p = e / (gampl * PI);
+    let mut q = 1f64 / (e * PI * gammi);
+    let pimu2 = 0.5 * pimu;
+    let fact3 = if pimu2.abs() < EPS { 1f64 } else { pimu2.sin() / pimu2 };
+    let r = PI * pimu2 * fact3 * fact3;
+    let mut c = 1f64;
+    let d = -x2 * x2;
+    let mut sum = ff + r * q;
+    let mut sum1 = p;
+    for i in 1 .. CF_MAX_ITER {
+        let i = i as f64;
+        ff = (i * ff + p + q) / (i * i - mu2);
+        c *= d / i;
+        p /= i - mu;
+        q /= i + mu;
+        let del = c * (ff + r * q);
+        sum += del;
+        sum1 += c * p - i * del;
+        if del.abs() < (1f64 + sum.abs()) * EPS {
+            break;
+        }
+    }
+    (-sum, -2f64 * sum1 / x)
+}
+
+/// `(cosh(e), sinh(e) / e, exp(e))` with `e = mu d` and `d = -ln(x/2)`, for Temme's series
+///
+/// `exp(e) = (x/2)^(-mu)` comes from `powf`, since the rounding of `e` is amplified by `|e|` for tiny `x`.
+fn bessel_temme_exp(mu: f64, x: f64, d: f64) -> (f64, f64, f64) {
+    let e = mu * d;
+    if e.abs() < 1f64 {
+        let fact2 = if e.abs() < EPS { 1f64 } else { e.sinh() / e };
+        return (e.cosh(), fact2, e.exp());
+    }
+    let ex = if x >= 2f64 * f64::MIN_POSITIVE { (0.5 * x).powf(-mu) } else { x.powf(-mu) * 2f64.powf(mu) };
+    (0.5 * (ex + 1f64 / ex), 0.5 * (ex - 1f64 / ex) / e, ex)
+}
+
+/// Temme's `(gamma_1, gamma_2, 1 / Gamma(1 + mu), 1 / Gamma(1 - mu))` for `|mu| <= 1/2`
+///
+/// `gamma_1 = (1 / Gamma(1 - mu) - 1 / Gamma(1 + mu)) / (2 mu)` is taken from the odd part of
+/// `ln Gamma(1 + mu)` to avoid cancellation.
+fn bessel_temme_gamma(mu: f64) -> (f64, f64, f64, f64) {
+    let gampl = 1f64 / gamma(1f64 + mu);
+    let gammi = 1f64 / gamma(1f64 - mu);
+    // s = (ln Gamma(1 + mu) - ln Gamma(1 - mu)) / (2 mu) from ln Gamma(2 + mu) - ln(1 + mu)
+    let mu2 = mu * mu;
+    let odd = LN_GAMMA_2P.iter().skip(1).step_by(2).rev().fold(0f64, |acc, &c| acc * mu2 + c);
+    let atanh = if mu == 0f64 { 1f64 } else { mu.atanh() / mu };
+    let s = ONE_MINUS_EULER + mu2 * odd - atanh;
+    let d = 2f64 * mu * s;
+    let exprel = if d == 0f64 { 1f64 } else { d.exp_m1() / d };
+    (gampl * exprel * s, 0.5 * (gammi + gampl), gampl, gammi)
+}
+
+/// `(J_nu(x), Y_nu(x))` by Hankel's asymptotic expansion for `x >= max(25, nu^2)`
+fn bessel_jy_asym(nu: f64, x: f64) -> (f64, f64) {
+    if x == f64::INFINITY {
+        return (0f64, 0f64);
+    }
+    let mu = 4f64 * nu * nu;
+    let mut p = 1f64;
+    let mut q = 0f64;
+    let mut term = 1f64;
+    for k in 1 .. CF_MAX_ITER {
+        let t = 2f64 * k as f64 - 1f64;
+        let next = term * (mu - t * t) / (8f64 * k as f64 * x);
+        if next.abs() >= term.abs() {
+            break;
+        }
+        term = next;
+        match k % 4 {
+            1 => q += term,
+            2 => p -= term,
+            3 => q -= term,
+            _ => p += term,
+        }
+        if term.abs() < EPS * p.abs() {
+            break;
+        }
+    }
+    // chi = x - (nu / 2 + 1/4) pi
+    let phi = 0.5 * nu + 0.25;
+    let (sx, cx) = x.sin_cos();
+    let (sp, cp) = (sin_pi(phi), cos_pi(phi));
+    let cchi = cx * cp + sx * sp;
+    let schi = sx * cp - cx * sp;
+    let s = (FRAC_2_PI / x).sqrt();
+    (s * (p * cchi - q * schi), s * (p * schi + q * cchi))
+}
+
+// =============================================================================
+// Modified Bessel functions
+// =============================================================================
+/// Modified Bessel function of the first kind `I_nu(x)`
+///
+/// `I_(-nu) = I_nu + 2 sin(nu pi) K_nu / pi` for `nu < 0`.
+/// Returns `NaN` for `x < 0` with non-integer `nu`.
+pub fn bessel_i(nu: f64, x: f64) -> f64 {
+    let ie = bessel_ie(nu, x);
+    if x.is_infinite() {
+        // ie is +0, or -0 for odd order at -inf
+        return ie.signum() * f64::INFINITY;
+    }
+    // Split so that I_nu does not overflow before exp(|x|) does
+    let h = (0.5 * x.abs()).exp();
+    ie * h * h
+}
+
+/// Modified Bessel function of the second kind `K_nu(x)`
+///
+/// `K_(-nu) = K_nu`. Returns `NaN` for `x < 0`.
+pub fn bessel_k(nu: f64, x: f64) -> f64 {
+    // Split so that exp(-x) does not go subnormal before K_nu does
+    let h = (-0.5 * x).exp();
+    bessel_ke(nu, x) * h * h
+}
+
+/// Exponentially scaled modified Bessel function of the first kind `I_nu(x) exp(-|x|)`
+pub fn bessel_ie(nu: f64, x: f64) -> f64 {
+    if nu.is_nan() || x.is_nan() {
+        return f64::NAN;
+    }
+    if x < 0f64 {
+        if nu != nu.round() {
+            return f64::NAN;
+        }
+        let ie = bessel_ie(nu, -x);
+        return if nu % 2f64 == 0f64 { ie } else { -ie };
+    }
+    if nu < 0f64 {
+        let (ie, ke) = bessel_ik_scaled(-nu, x);
+        let s = sin_pi(nu);
+        if s == 0f64 {
+            ie
+        } else {
+            ie - FRAC_2_PI * s * ke * (-2f64 * x).exp()
+        }
+    } else {
+        bessel_ik_scaled(nu, x).0
+    }
+}
+
+/// Exponentially scaled modified Bessel function of the second kind `K_nu(x) exp(x)`
+pub fn bessel_ke(nu: f64, x: f64) -> f64 {
+    if nu.is_nan() || x.is_nan() || x < 0f64 {
+        return f64::NAN;
+    }
+    bessel_ik_scaled(nu.abs(), x).1
+}
+
+/// Logarithm of modified Bessel function of the first kind `ln I_nu(x)`
+///
+/// For `nu >= 0` and `x >= 0` : `x + ln(I_nu(x) e^(-x))`, or `ln Gamma` with the power series
+/// where `I_nu(x)` underflows (`x` small against `nu`). Negative orders go through `bessel_ie`.
+pub fn ln_bessel_i(nu: f64, x: f64) -> f64 {
+    let ie = bessel_ie(nu, x);
+    if ie >= FPMIN || nu < 0f64 || x <= 0f64 {
+        return ie.ln() + x.abs();
+    }
+    nu * (x.ln() - LN_2) - ln_gamma(nu + 1f64) + bessel_series_sum(nu, 0.25 * x * x).ln()
+}
+
+/// Scaled `(I_nu(x) e^(-x), K_nu(x) e^x)` for `nu >= 0`, `x >= 0`
+///
+/// * `x < 2` : power series for `I_nu`, Temme's series for `K_mu`, `K_(mu+1)` with `|mu| <= 1/2`
+/// * `x < max(25, nu^2)` : Numerical Recipes `besselik`. CF1 gives `I'_nu / I_nu`, downward
+///   recurrence carries it to `mu`, Steed's CF2 fixes `K_mu`, `K_(mu+1)` and the Wronskian `I_mu`
+/// * otherwise : asymptotic expansion
+///
+/// `K` is recurred upward from `mu` to `nu`.
+fn bessel_ik_scaled(nu: f64, x: f64) -> (f64, f64) {
+    if x == 0f64 {
+        return (if nu == 0f64 { 1f64 } else { 0f64 }, f64::INFINITY);
+    }
+    if x >= BESSEL_ASYM_MIN.max(nu * nu) {
+        return bessel_ik_asym(nu, x);
+    }
+    let nl = (nu + 0.5).floor() as usize;
+    let mu = nu - nl as f64;
+    if x < BESSEL_TEMME_MAX {
+        let (kmu, k1) = bessel_k_temme(mu, x);
+        let ex = x.exp();
+        let i = bessel_series_prefactor(nu, x) * bessel_series_sum(nu, 0.25 * x * x) / ex;
+        return (i, bessel_recur_up(mu, x, kmu * ex, k1 * ex, nl, 1f64));
+    }
+    let xi = 1f64 / x;
+    let xi2 = 2f64 * xi;
+
+    // CF1 by modified Lentz
+    let mut h = (nu * xi).max(FPMIN);
+    let mut d = 0f64;
+    let mut c = h;
+    for i in 1 .. CF_MAX_ITER {
+        let b = xi2 * (nu + i as f64);
+        d = 1f64 / (b + d);
+        c = b + 1f64 / c;
+        let del = c * d;
+        h *= del;
+        if (del - 1f64).abs() <= EPS {
+            break;
+        }
+    }
+
+    let mut ril = FPMIN;
+    let mut ripl = h * ril;
+    let mut ril1 = ril;
+    for l in 0 .. nl {
+        // Coefficients not accumulated : nl can be large
+        let ritemp = (nu - l as f64) * xi * ril + ripl;
+        ripl = (nu - (l + 1) as f64) * xi * ritemp + ril;
+        ril = ritemp;
+        if ril > BESSEL_RESCALE {
+            // I_nu underflows relative to I_mu
+            ril /= BESSEL_RESCALE;
+            ripl /= BESSEL_RESCALE;
+            ril1 /= BESSEL_RESCALE;
+        }
+    }
+    let f = ripl / ril;
+
+    // CF2 with Thompson-Barnett summation of K_mu, scaled by e^x
+    let mut b = 2f64 * (1f64 + x);
+    let mut d = 1f64 / b;
+    let mut delh = d;
+    let mut h = d;
+    let mut q1 = 0f64;
+    let mut q2 = 1f64;
+    let a1 = 0.25 - mu * mu;
+    let mut q = a1;
+    let mut c = a1;
+    let mut a = -a1;
+    let mut s = 1f64 + q * delh;
+    for i in 1 .. CF_MAX_ITER {
+        let i = i as f64;
+        a -= 2f64 * i;
+        c = -a * c / (i + 1f64);
+        let qnew = (q1 - b * q2) / a;
+        q1 = q2;
+        q2 = qnew;
+        q += c * qnew;
+        b += 2f64;
+        d = 1f64 / (b + a * d);
+        delh *= b * d - 1f64;
+        h += delh;
+        let dels = q * delh;
+        s += dels;
+        if (dels / s).abs() <= EPS {
+            break;
+        }
+    }
+    let rkmu = (PI / (2f64 * x)).sqrt() / s;
+    let rk1 = rkmu * (mu + x + 0.5 - a1 * h) * xi;
+    let rkmup = mu * xi * rkmu - rk1;
+    let rimu = xi / (f * rkmu - rkmup);
+    (rimu * (ril1 / ril), bessel_recur_up(mu, x, rkmu, rk1, nl, 1f64))
+}
+
+/// Temme's series for `(K_mu(x), K_(mu+1)(x))` with `|mu| <= 1/2`, `x < 2`
+fn bessel_k_temme(mu: f64, x: f64) -> (f64, f64) {
+    let x2 = 0.5 * x;
+    let pimu = PI * mu;
+    let fact = if pimu.abs() < EPS { 1f64 } else { pimu / pimu.sin() };
+    // -ln(x/2), where x/2 itself underflows for the smallest subnormal x
+    let d = LN_2 - x.ln();
+    let (ch, fact2, e) = bessel_temme_exp(mu, x, d);
+    let (gam1, gam2, gampl, gammi) = bessel_temme_gamma(mu);
+    let mut ff = fact * (gam1 * ch + gam2 * fact2 * d);
+    let mut sum = ff;
+    let mut p = 0.5 * e / gampl;
+    let mut q = 0.5 / (e * gammi);
+    let mut c = 1f64;
+    let d = x2 * x2;
+    let mut sum1 = p;
+    for i in 1 .. CF_MAX_ITER {
+        let i = i as f64;
+        ff = (i * ff + p + q) / (i * i - mu * mu);
+        c *= d / i;
+        p /= i - mu;
+        q /= i + mu;
+        let del = c * ff;
+        sum += del;
+        sum1 += c * (p - i * ff);
+        if del.abs() < sum.abs() * EPS {
+            break;
+        }
+    }
+    (sum, 2f64 * sum1 / x)
+}
+
+/// `(x/2)^nu / Gamma(nu + 1)`, the leading term of `I_nu(x)`
+///
+/// `Gamma(nu + 1) = nu Gamma(nu)` for `nu >= 1`, as the rounding of `nu + 1` would be amplified by `psi(nu + 1)`.
+fn bessel_series_prefactor(nu: f64, x: f64) -> f64 {
+    // x/2 drops bits once x is subnormal
+    let p = if x >= 2f64 * f64::MIN_POSITIVE { (0.5 * x).powf(nu) } else { x.powf(nu) * 0.5f64.powf(nu) };
+    if nu + 1f64 < GAMMA_MAX && p >= FPMIN {
+        if nu >= 1f64 { p / (nu * gamma(nu)) } else { p / gamma(nu + 1f64) }
+    } else {
+        let lg = if nu >= 1f64 { ln_gamma(nu) + nu.ln() } else { ln_gamma(nu + 1f64) };
+        (nu * (x.ln() - LN_2) - lg).exp()
+    }
+}
+
+/// `sum_k y^k / (k! (nu + 1)_k)` with `y = x^2/4`, the series of `I_nu(x)` after the prefactor
+fn bessel_series_sum(nu: f64, y: f64) -> f64 {
+    let mut term = 1f64;
+    let mut sum = 1f64;
+    for k in 1 .. CF_MAX_ITER {
+        let k = k as f64;
+        term *= y / (k * (nu + k));
+        sum += term;
+        if term.abs() < EPS * sum.abs() {
+            break;
+        }
+    }
+    sum
+}
+
+/// Upward recurrence `f_(k+1) = 2 (mu + k) f_k / x + s f_(k-1)` from `(f_mu, f_(mu+1))` to `f_(mu+n)`
+///
+/// Stable for `K` (`s = 1`). Stops once overflowed.
+fn bessel_recur_up(mu: f64, x: f64, f0: f64, f1: f64, n: usize, s: f64) -> f64 {
+    let xi2 = 2f64 / x;
+    let mut fm = f0;
+    let mut f = f1;
+    for i in 1 ..= n {
+        if f.is_infinite() {
+            return f;
+        }
+        let fp = (mu + i as f64) * xi2 * f + s * fm;
+        fm = f;
+        f = fp;
+    }
+    fm
+}
+
+/// Scaled `(I_nu(x) e^(-x), K_nu(x) e^x)` by the asymptotic expansion for `x >= max(25, nu^2)`
+fn bessel_ik_asym(nu: f64, x: f64) -> (f64, f64) {
+    if x == f64::INFINITY {
+        return (0f64, 0f64);
+    }
+    let mu = 4f64 * nu * nu;
+    let mut si = 1f64;
+    let mut sk = 1f64;
+    let mut term = 1f64;
+    for k in 1 .. CF_MAX_ITER {
+        let t = 2f64 * k as f64 - 1f64;
+        let next = term * (mu - t * t) / (8f64 * k as f64 * x);
+        if next.abs() >= term.abs() {
+            break;
+        }
+        term = next;
+        sk += term;
+        si += if k % 2 == 1 { -term } else { term };
+        if term.abs() < EPS * si.abs() {
+            break;
+        }
+    }
+    (si / (2f64 * PI * x).sqrt(), sk * (FRAC_PI_2 / x).sqrt())
+}
+
+// =============================================================================
+// Spherical Bessel functions
+// =============================================================================
+/// Spherical Bessel function of the first kind `j_n(x) = sqrt(pi / 2x) J_(n+1/2)(x)`
+pub fn spherical_jn(n: usize, x: f64) -> f64 {
+    spherical_jn_array(n, x)[n]
+}
+
+/// Spherical Bessel function of the second kind `y_n(x) = sqrt(pi / 2x) Y_(n+1/2)(x)`
+pub fn spherical_yn(n: usize, x: f64) -> f64 {
+    spherical_yn_array(n, x)[n]
+}
+
+/// Modified spherical Bessel function of the first kind `i_n(x) = sqrt(pi / 2x) I_(n+1/2)(x)`
+pub fn spherical_in(n: usize, x: f64) -> f64 {
+    spherical_in_array(n, x)[n]
+}
+
+/// Modified spherical Bessel function of the second kind `k_n(x) = sqrt(pi / 2x) K_(n+1/2)(x)`
+pub fn spherical_kn(n: usize, x: f64) -> f64 {
+    spherical_kn_array(n, x)[n]
+}
+
+/// Spherical Bessel functions of the first kind `j_0(x), ..., j_n(x)`
+///
+/// Upward recurrence from `j_0`, `j_1` for `n <= |x|`, otherwise Miller's downward recurrence
+/// normalized by the larger of `j_0`, `j_1`.
+pub fn spherical_jn_array(n: usize, x: f64) -> Vec<f64> {
+    let ax = x.abs();
+    let mut j = if ax.is_infinite() {
+        vec![0f64; n + 1]
+    } else if 0.25 * ax * ax < EPS {
+        spherical_small(n, ax)
+    } else {
+        let (sn, cs) = ax.sin_cos();
+        let j0 = sn / ax;
+        let j1 = (j0 - cs) / ax;
+        if n as f64 <= ax {
+            spherical_recur_up(n, ax, j0, j1, -1f64)
+        } else {
+            let m = n + (BESSEL_MILLER_ACC * n as f64).sqrt() as usize;
+            let (f, ex) = spherical_miller(n, ax, m, -1f64);
+            let (scale, e) = if j0.abs() >= j1.abs() { (j0 / f[0], ex[0]) } else { (j1 / f[1], ex[1]) };
+            f.iter().zip(ex).map(|(&v, ek)| mul_pow2(v * scale, ek - e)).collect()
+        }
+    };
+    if x < 0f64 {
+        // j_n(-x) = (-1)^n j_n(x)
+        j.iter_mut().skip(1).step_by(2).for_each(|v| *v = -*v);
+    }
+    j
+}
+
+/// Spherical Bessel functions of the second kind `y_0(x), ..., y_n(x)` by upward recurrence
+pub fn spherical_yn_array(n: usize, x: f64) -> Vec<f64> {
+    let ax = x.abs();
+    let mut y = if ax.is_infinite() {
+        vec![0f64; n + 1]
+    } else {
+        let (sn, cs) = ax.sin_cos();
+        let y0 = -cs / ax;
+        spherical_recur_up(n, ax, y0, (y0 - sn) / ax, -1f64)
+    };
+    if x < 0f64 {
+        // y_n(-x) = (-1)^(n+1) y_n(x)
+        y.iter_mut().step_by(2).for_each(|v| *v = -*v);
+    }
+    y
+}
+
+/// Modified spherical Bessel functions of the first kind `i_0(x), ..., i_n(x)`
+///
+/// Miller's downward recurrence normalized by `i_0(x) = sinh(x) / x = e^x (1 - e^(-2x)) / (2x)`.
+/// The powers of two of `e^x` and of the recurrence are applied last, so that `i_k` stays finite
+/// where `i_0` already overflows and is not lost where `i_k / i_0` underflows.
+pub fn spherical_in_array(n: usize, x: f64) -> Vec<f64> {
+    let ax = x.abs();
+    let mut i = if ax.is_infinite() || (ax > 2f64 * LN_MAX && n as f64 <= ax) {
+        // i_k(x) >= i_n(x) ~ exp(x (sqrt(1 + t^2) - t asinh(t))) / (2x) with t = n / x <= 1, so all overflow
+        vec![f64::INFINITY; n + 1]
+    } else if 0.25 * ax * ax < EPS {
+        spherical_small(n, ax)
+    } else {
+        let m = n + (BESSEL_MILLER_ACC * ax.max(n as f64)).sqrt() as usize;
+        let (f, ex) = spherical_miller(n, ax, m, 1f64);
+        // e^x = 2^q e^r with |r| <= ln(2) / 2, q ln(2) subtracted in two parts (Cody & Waite)
+        let q = (ax / LN_2).round();
+        let r = (ax - q * LN2_HI) - q * LN2_LO;
+        let scale = -(-2f64 * ax).exp_m1() / (2f64 * ax) * r.exp() / f[0];
+        let e0 = ex[0] - q as i32;
+        f.iter().zip(ex).map(|(&v, ek)| mul_pow2(v * scale, ek - e0)).collect()
+    };
+    if x < 0f64 {
+        // i_n(-x) = (-1)^n i_n(x)
+        i.iter_mut().skip(1).step_by(2).for_each(|v| *v = -*v);
+    }
+    i
+}
+
+/// Modified spherical Bessel functions of the second kind `k_0(x), ..., k_n(x)` by upward recurrence
+///
+/// Returns `NaN` for `x < 0`.
+pub fn spherical_kn_array(n: usize, x: f64) -> Vec<f64> {
+    if x.is_nan() || x < 0f64 {
+        return vec![f64::NAN; n + 1];
+    }
+    let k0 = FRAC_PI_2 * (-x).exp() / x;
+    spherical_recur_up(n, x, k0, k0 * (1f64 + 1f64 / x), 1f64)
+}
+
+/// Riccati-Bessel function `psi_n(x) = x j_n(x)`
+pub fn riccati_psi(n: usize, x: f64) -> f64 {
+    x * spherical_jn(n, x)
+}
+
+/// Riccati-Bessel function `xi_n(x) = x h_n^(1)(x) = x (j_n(x) + i y_n(x))`
+pub fn riccati_xi(n: usize, x: f64) -> Complex {
+    Complex::new(x * spherical_jn(n, x), x * spherical_yn(n, x))
+}
+
+/// Riccati-Bessel functions `psi_0(x), ..., psi_n(x)`
+pub fn riccati_psi_array(n: usize, x: f64) -> Vec<f64> {
+    spherical_jn_array(n, x).into_iter().map(|j| x * j).collect()
+}
+
+/// Riccati-Bessel functions `xi_0(x), ..., xi_n(x)`
+pub fn riccati_xi_array(n: usize, x: f64) -> Vec<Complex> {
+    let y = spherical_yn_array(n, x);
+    spherical_jn_array(n, x)
+        .into_iter()
+        .zip(y)
+        .map(|(j, y)| Complex::new(x * j, x * y))
+        .collect()
+}
+
+/// Leading terms `x^k / (2k + 1)!!` of `j_k(x)` and `i_k(x)` for `x^2 / 4 < EPS`
+fn spherical_small(n: usize, x: f64) -> Vec<f64> {
+    let mut f = vec![1f64; n + 1];
+    for k in 1 ..= n {
+        f[k] = f[k - 1] * x / (2f64 * k as f64 + 1f64);
+    }
+    f
+}
+
+/// Upward recurrence `f_(k+1) = (2k + 1) f_k / x + s f_(k-1)` from `(f_0, f_1)` to `f_n`
+///
+/// Stable for `y` (`s = -1`), `k` (`s = 1`) and for `j` below `k = x`. Overflowed values are carried on.
+fn spherical_recur_up(n: usize, x: f64, f0: f64, f1: f64, s: f64) -> Vec<f64> {
+    let mut f = Vec::with_capacity(n + 1);
+    f.push(f0);
+    if n >= 1 {
+        f.push(f1);
+    }
+    for k in 1 .. n {
+        let next = if f[k].is_infinite() {
+            f[k]
+        } else {
+            (2f64 * k as f64 + 1f64) / x * f[k] + s * f[k - 1]
+        };
+        f.push(next);
+    }
+    f
+}
+
+/// Miller's downward recurrence `f_(k-1) = (2k + 1) f_k / x + s f_(k+1)` from `f_m = 1`,
+/// giving unnormalized `f_k 2^(e_k)` for `k = 0, ..., n` for `j` (`s = -1`) or `i` (`s = 1`)
+///
+/// The running values are kept below `2^64` by exact power of two scaling, recorded in `e_k`
+/// instead of being applied to the stored values, which would underflow for `i`.
+fn spherical_miller(n: usize, x: f64, m: usize, s: f64) -> (Vec<f64>, Vec<i32>) {
+    let scale = 2f64.powi(64);
+    let mut f = vec![0f64; n + 1];
+    let mut ex = vec![0; n + 1];
+    let mut e = 0;
+    let mut fp = 0f64;
+    let mut fk = 1f64;
+    for k in (1 ..= m).rev() {
+        if k <= n {
+            f[k] = fk;
+            ex[k] = e;
+        }
+        let fm = (2f64 * k as f64 + 1f64) / x * fk + s * fp;
+        fp = fk;
+        fk = fm;
+        if fk.abs() > scale {
+            fk /= scale;
+            fp /= scale;
+            e += 64;
+        }
+    }
+    f[0] = fk;
+    ex[0] = e;
+    (f, ex)
+}
+
+/// `x 2^e`, exact unless the result overflows or underflows
+fn mul_pow2(x: f64, e: i32) -> f64 {
+    // 2^e itself may be out of range, so it is applied in two halves
+    let e = e.clamp(-2 * 1023, 2 * 1023);
+    x * 2f64.powi(e / 2) * 2f64.powi(e - e / 2)
+}
+
+// =============================================================================
+// Incomplete Beta function
+// =============================================================================
+pub fn betai(a: f64, b: f64, x: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betai");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine betai");
+    if x == 0f64 || x == 1f64 {
+        return x;
+    }
+    betai_pq(a, b, x).0
+}
+
+/// Complementary regularized incomplete beta function `1 - I_x(a,b)`
+///
+/// Computed directly on the upper tail, so tiny p-values are not lost in `1 - betai(a, b, x)`.
+pub fn betaic(a: f64, b: f64, x: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betaic");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine betaic");
+    if x == 0f64 || x == 1f64 {
+        return 1f64 - x;
+    }
+    betai_pq(a, b, x).1
+}
+
+/// Incomplete beta function `B_x(a,b) = B(a,b) I_x(a,b)`
+pub fn incbeta(a: f64, b: f64, x: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine incbeta");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine incbeta");
+    if x == 0f64 {
+        return 0f64;
+    }
+    beta(a, b) * betai(a, b, x)
+}
+
+/// Logarithm of regularized incomplete beta function `ln I_x(a,b)`
+///
+/// When `I_x(a,b)` underflows, the continued fraction is combined with the logarithm of
+/// its prefactor `x^a (1-x)^b / B(a,b)`, so the prefactor is never exponentiated.
+pub fn ln_betai(a: f64, b: f64, x: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine ln_betai");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine ln_betai");
+    if x == 0f64 {
+        return f64::NEG_INFINITY;
+    } else if x == 1f64 {
+        return 0f64;
+    }
+    let (p, q) = betai_pq(a, b, x);
+    if q < 0.5 {
+        (-q).ln_1p()
+    } else if p >= f64::MIN_POSITIVE {
+        p.ln()
+    } else {
+        // Far below the mean, where the continued fraction converges quickly
+        let y = 1f64 - x;
+        let (m, e) = betai_prefactor_ln(a, b, x, y);
+        m.ln() + e + betacf(a, b, x, y, beta_lambda(a, b, x, y)).ln()
+    }
+}
+
+/// Probability mass `I_x2(a,b) - I_x1(a,b)` of the Beta distribution between two points
+///
+/// The difference is taken on the tail with the smaller value. If it still cancels, the density is
+/// integrated directly in `ln(x / (1 - x))`, so narrow intervals keep full relative precision.
+pub fn betai_diff(a: f64, b: f64, x1: f64, x2: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betai_diff");
+    assert!((0f64..=1f64).contains(&x1) && (0f64..=1f64).contains(&x2), "Bad x in routine betai_diff");
+    if x1 > x2 {
+        return -betai_diff(a, b, x2, x1);
+    } else if x1 == x2 {
+        return 0f64;
+    }
+    let (p1, q1) = if x1 == 0f64 { (0f64, 1f64) } else { betai_pq(a, b, x1) };
+    let (p2, q2) = if x2 == 1f64 { (1f64, 0f64) } else { betai_pq(a, b, x2) };
+    let (d, scale) = if p2 < q1 { (p2 - p1, p2) } else { (q1 - q2, q1) };
+    if d >= 0.5 * scale {
+        d
+    } else {
+        // I_x(a,b) = int x^a (1-x)^b / B(a,b) d(ln(x / (1 - x))), with u = ln(x / (1 - x)) - ln(x1 / (1 - x1))
+        let h = x2 - x1;
+        let y1 = 1f64 - x1;
+        let l = (h / x1).ln_1p() - (-h / y1).ln_1p();
+        let f = |u: f64| {
+            let v = x1 * u.exp();
+            betai_prefactor(a, b, v / (y1 + v), y1 / (y1 + v))
+        };
+        gauss_legendre(f, 0f64, l)
+    }
+}
+
+/// `(I_x(a,b), 1 - I_x(a,b))`, each computed without cancellation when it is the smaller one
+///
+/// Selects the method as in DiDonato & Morris (1992), Algorithm 708: power series for small `x` or small `b`,
+/// `bgrat` when one parameter is large and the other is small, `basym` when both are large and `x`
+/// is near the mean, and the continued fraction otherwise.
+fn betai_pq(a: f64, b: f64, x: f64) -> (f64, f64) {
+    let y = 1f64 - x;
+    if a.max(b) < 1e-3 * EPS {
+        return (b / (a + b), a / (a + b));
+    }
+    let (mut a0, mut b0, mut x0, mut y0) = (a, b, x, y);
+    let mut swap = false;
+    let (w, w1);
+    if a0.min(b0) <= 1f64 {
+        if x > 0.5 {
+            swap = true;
+            (a0, b0, x0, y0) = (b, a, y, x);
+        }
+        if b0 < EPS.min(EPS * a0) {
+            w = fpser(a0, b0, x0);
+            w1 = 1f64 - w;
+        } else if a0 < EPS.min(EPS * b0) && b0 * x0 <= 1f64 {
+            w1 = apser(a0, b0, x0);
+            w = 1f64 - w1;
+        } else {
+            let series = if a0.max(b0) <= 1f64 {
+                a0 >= 0.2f64.min(b0) || x0.powf(a0) <= 0.9
+            } else {
+                b0 <= 1f64 || (x0 < 0.1 && (x0 * b0).powf(a0) <= 0.7)
+            };
+            if series {
+                w = bpser(a0, b0, x0, y0);
+                w1 = 1f64 - w;
+            } else if x0 >= 0.3 {
+                w1 = bpser(b0, a0, y0, x0);
+                w = 1f64 - w1;
+            } else {
+                // Raise b0 above 15 and use the expansion for large b0 on the complement
+                let mut u = 0f64;
+                if b0 <= 15f64 {
+                    u = bup(b0, a0, y0, x0, 20);
+                    b0 += 20f64;
+                }
+                w1 = bgrat(b0, a0, y0, x0, u);
+                w = 1f64 - w1;
+            }
+        }
+    } else {
+        // lambda = a - (a + b) x is the distance from the mean
+        let mut lambda = beta_lambda(a, b, x, y);
+        if lambda < 0f64 {
+            swap = true;
+            (a0, b0, x0, y0) = (b, a, y, x);
+            lambda = -lambda;
+        }
+        if b0 < 40f64 && b0 * x0 <= 0.7 {
+            w = bpser(a0, b0, x0, y0);
+        } else if b0 < 40f64 {
+            // Reduce b0 to (0, 1] : I_x(a0, b0 + n) = I_x(a0, b0) + bup(b0, a0, y0, x0, n)
+            let mut n = b0.floor();
+            b0 -= n;
+            if b0 == 0f64 {
+                n -= 1f64;
+                b0 = 1f64;
+            }
+            let mut u = bup(b0, a0, y0, x0, n as usize);
+            if x0 <= 0.7 {
+                u += bpser(a0, b0, x0, y0);
+            } else {
+                if a0 <= 15f64 {
+                    u += bup(a0, b0, x0, y0, 20);
+                    a0 += 20f64;
+                }
+                u = bgrat(a0, b0, x0, y0, u);
+            }
+            w = u;
+        } else if a0.min(b0) > 100f64 && lambda <= 0.03 * a0.min(b0) {
+            w = basym(a0, b0, lambda);
+        } else {
+            w = betai_prefactor(a0, b0, x0, y0) * betacf(a0, b0, x0, y0, lambda);
+        }
+        w1 = 1f64 - w;
+    }
+    if swap { (w1, w) } else { (w, w1) }
+}
+
+/// `a - (a + b) x`, accurate when `a` and `b` are large and `x` is near the mean
+fn beta_lambda(a: f64, b: f64, x: f64, y: f64) -> f64 {
+    // a + b = c + ec exactly
+    let c = a + b;
+    let bv = c - a;
+    let ec = (a - (c - bv)) + (b - bv);
+    if x <= y {
+        (-c).mul_add(x, a) - ec * x
+    } else {
+        c.mul_add(y, -b) + ec * y
+    }
+}
+
+/// `(ln x, ln y)` for `x + y = 1`, using whichever of `x`, `y` is smaller and therefore exact
+fn ln_xy(x: f64, y: f64) -> (f64, f64) {
+    if x <= y { (x.ln(), (-x).ln_1p()) } else { ((-y).ln_1p(), y.ln()) }
+}
+
+/// Power series of `I_x(a,b)` for `b <= 1` or `b x <= 0.7`
+///
+/// `I_x(a,b) = x^a / (a B(a,b)) (1 + a sum_(n>=1) (1-b)_n x^n / (n! (a + n)))`
+fn bpser(a: f64, b: f64, x: f64, y: f64) -> f64 {
+    let pre = betai_prefactor(a, b, x, y) / (b * ln_xy(x, y).1).exp() / a;
+    if pre == 0f64 {
+        return 0f64;
+    }
+    let mut c = 1f64;
+    let mut sum = 0f64;
+    for n in 1 .. CF_MAX_ITER {
+        let n = n as f64;
+        c *= (1f64 - b / n) * x;
+        let w = c / (a + n);
+        sum += w;
+        if w.abs() <= EPS / a * (1f64 + a * sum).abs() {
+            break;
+        }
+    }
+    pre * (1f64 + a * sum)
+}
+
+/// `I_x(a,b)` for `b < min(eps, eps a)` and `x <= 1/2`, where `1 / B(a,b) = b`
+fn fpser(a: f64, b: f64, x: f64) -> f64 {
+    let pre = b / a * (a * x.ln()).exp();
+    if pre == 0f64 {
+        return 0f64;
+    }
+    let mut t = x;
+    let mut an = a + 1f64;
+    let mut sum = t / an;
+    loop {
+        an += 1f64;
+        t *= x;
+        let c = t / an;
+        sum += c;
+        if c <= EPS / a * sum {
+            break;
+        }
+    }
+    pre * (1f64 + a * sum)
+}
+
+/// `1 - I_x(a,b)` for `a < min(eps, eps b)` and `b x <= 1`
+///
+/// `1 - I_x(a,b) = -a (ln x + psi(b) + gamma + sum_(j>=1) (1-b)_j x^j / (j j!)) + O(a^2)`
+fn apser(a: f64, b: f64, x: f64) -> f64 {
+    let bx = b * x;
+    let mut t = x - bx;
+    let c = if b * EPS <= 0.02 {
+        x.ln() + digamma(b) + EULER + t
+    } else {
+        bx.ln() + EULER + t
+    };
+    let tol = 5f64 * EPS * c.abs();
+    let mut s = 0f64;
+    for j in 2 .. {
+        let j = j as f64;
+        t *= x - bx / j;
+        let aj = t / j;
+        s += aj;
+        if aj.abs() <= tol {
+            break;
+        }
+    }
+    -a * (c + s)
+}
+
+/// `I_x(a,b) - I_x(a+n,b)`
+///
+/// Finite sum of `x^(a+i) (1-x)^b / ((a+i) B(a+i,b))` for `i = 0, ..., n-1`.
+/// The terms can grow by many orders of magnitude, so the sum is combined with the logarithm
+/// of the prefactor when the first term underflows.
+fn bup(a: f64, b: f64, x: f64, y: f64, n: usize) -> f64 {
+    if n == 0 {
+        return 0f64;
+    }
+    let mut d = 1f64;
+    let mut w = 1f64;
+    for i in 1 .. n {
+        let l = (i - 1) as f64;
+        d *= (a + b + l) / (a + 1f64 + l) * x;
+        w += d;
+    }
+    let (m, e) = betai_prefactor_ln(a, b, x, y);
+    let pre = m * e.exp() / a;
+    if pre >= f64::MIN_POSITIVE {
+        pre * w
+    } else {
+        m / a * (e + w.ln()).exp()
+    }
+}
+
+/// Asymptotic expansion of `I_x(a,b)` for large `a` and `b <= 1`, added to `w`
+///
+/// `I_x(a,b) = H(b,u) Gamma(a+b) / (Gamma(a) T^b) sum_n p_n J_n(b,u)`
+/// with `T = a + (b-1)/2`, `u = -T ln x` and `J_0 = Q(b,u) / H(b,u)` (DiDonato & Morris, eq. 9).
+fn bgrat(a: f64, b: f64, x: f64, y: f64, w: f64) -> f64 {
+    let bm1 = b - 1f64;
+    let t = a + 0.5 * bm1;
+    let lx = if y < 0.35 { (-y).ln_1p() } else { x.ln() };
+    let u = -t * lx;
+    let h = gamma_regularized_prefactor(b, u);
+    if h <= f64::MIN_POSITIVE {
+        return w;
+    }
+    let prefix = h * gamma_delta_ratio(a, b) / t.powf(b);
+    let mut p = [0f64; 30];
+    p[0] = 1f64;
+    let mut j = gammq(b, u) / h;
+    let mut sum = w + prefix * j;
+    let mut tnp1 = 1;
+    let lx2 = (0.5 * lx).powi(2);
+    let mut lxp = 1f64;
+    let t4 = 4f64 * t * t;
+    let mut b2n = b;
+    for n in 1 .. p.len() {
+        // p_n = (b-1) / (2n+1)! + 1/n sum_(m=1)^(n-1) (m b - n) p_(n-m) / (2m+1)!
+        tnp1 += 2;
+        let mut pn = 0f64;
+        for m in 1 .. n {
+            pn += (m as f64 * b - n as f64) * p[n - m] / factorial_f64(2 * m + 1);
+        }
+        p[n] = pn / n as f64 + bm1 / factorial_f64(tnp1);
+        // J_n from J_(n-1)
+        j = (b2n * (b2n + 1f64) * j + (u + b2n + 1f64) * lxp) / t4;
+        lxp *= lx2;
+        b2n += 2f64;
+        let r = prefix * p[n] * j;
+        sum += r;
+        if r.abs() <= EPS * sum.abs() {
+            break;
+        }
+    }
+    sum
+}
+
+/// Asymptotic expansion of `I_x(a,b)` for large `a` and `b` with `lambda = a - (a + b) x >= 0` small
+///
+/// DiDonato & Morris (1992), `BASYM`.
+fn basym(a: f64, b: f64, lambda: f64) -> f64 {
+    const NUM: usize = 20;
+    const E0: f64 = FRAC_2_SQRT_PI;
+    // 2^(-3/2)
+    const E1: f64 = 0.353553390593273762;
+    let mut a0 = [0f64; NUM + 1];
+    let mut b0 = [0f64; NUM + 1];
+    let mut c = [0f64; NUM + 1];
+    let mut d = [0f64; NUM + 1];
+
+    let f = -a * ln_1p_mx(-lambda / a) - b * ln_1p_mx(lambda / b);
+    let t = (-f).exp();
+    if t == 0f64 {
+        return 0f64;
+    }
+    let z0 = f.sqrt();
+    let z = 0.5 * z0 / E1;
+    let z2 = f + f;
+    let (h, r0, r1, w0) = if a < b {
+        let h = a / b;
+        (h, 1f64 / (1f64 + h), (b - a) / b, 1f64 / (a * (1f64 + h)).sqrt())
+    } else {
+        let h = b / a;
+        (h, 1f64 / (1f64 + h), (b - a) / a, 1f64 / (b * (1f64 + h)).sqrt())
+    };
+
+    a0[0] = 2f64 / 3f64 * r1;
+    c[0] = -0.5 * a0[0];
+    d[0] = -c[0];
+    let mut j0 = 0.5 / E0 * erfccheb_scaled(z0);
+    let mut j1 = E1;
+    let mut sum = j0 + d[0] * w0 * j1;
+
+    let mut s = 1f64;
+    let h2 = h * h;
+    let mut hn = 1f64;
+    let mut w = w0;
+    let mut znm1 = z;
+    let mut zn = z2;
+    for n in (2 ..= NUM).step_by(2) {
+        hn *= h2;
+        a0[n - 1] = 2f64 * r0 * (1f64 + h * hn) / (n as f64 + 2f64);
+        s += hn;
+        a0[n] = 2f64 * r1 * s / (n as f64 + 3f64);
+        for i in n ..= n + 1 {
+            let r = -0.5 * (i as f64 + 1f64);
+            b0[0] = r * a0[0];
+            for m in 2 ..= i {
+                let mut bsum = 0f64;
+                for jj in 1 .. m {
+                    bsum += (jj as f64 * r - (m - jj) as f64) * a0[jj - 1] * b0[m - jj - 1];
+                }
+                b0[m - 1] = r * a0[m - 1] + bsum / m as f64;
+            }
+            c[i - 1] = b0[i - 1] / (i as f64 + 1f64);
+            let mut dsum = 0f64;
+            for jj in 1 .. i {
+                dsum += d[i - jj - 1] * c[jj - 1];
+            }
+            d[i - 1] = -(dsum + c[i - 1]);
+        }
+        j0 = E1 * znm1 + (n as f64 - 1f64) * j0;
+        j1 = E1 * zn + n as f64 * j1;
+        znm1 *= z2;
+        zn *= z2;
+        w *= w0;
+        let t0 = d[n - 1] * w * j0;
+        w *= w0;
+        let t1 = d[n] * w * j1;
+        sum += t0 + t1;
+        if t0.abs() + t1.abs() <= EPS * sum {
+            break;
+        }
+    }
+    let u = (stirling_correction(a + b) - stirling_correction(a) - stirling_correction(b)).exp();
+    E0 * t * u * sum
+}
+
+/// Continued fraction beta
+fn betacf(a: f64, b: f64, x: f64, y: f64, lambda: f64) -> f64 {
+    // DiDonato & Morris (1992), BFRAC : lambda = a - (a + b) x is passed in to avoid cancellation near the mean
+    let c = 1f64 + lambda;
+    let c0 = b / a;
+    let c1 = 1f64 + 1f64 / a;
+    let yp1 = y + 1f64;
+    let mut p = 1f64;
+    let mut s = a + 1f64;
+    let mut an = 0f64;
+    let mut bn = 1f64;
+    let mut anp1 = 1f64;
+    let mut bnp1 = c / c1;
+    let mut r = c1 / c;
+    for n in 1 .. CF_MAX_ITER {
+        let n = n as f64;
+        let t = n / a;
+        let w = n * (b - n) * x;
+        let e = a / s;
+        let alpha = p * (p + c0) * e * e * (w * x);
+        let e = (1f64 + t) / (c1 + t + t);
+        let beta = n + w / s + e * (c + n * yp1);
+        p = 1f64 + t;
+        s += 2f64;
+        let t = alpha * an + beta * anp1;
+        an = anp1;
+        anp1 = t;
+        let t = alpha * bn + beta * bnp1;
+        bn = bnp1;
+        bnp1 = t;
+        let r0 = r;
+        r = anp1 / bnp1;
+        if (r - r0).abs() <= EPS * r {
+            break;
+        }
+        an /= bnp1;
+        bn /= bnp1;
+        anp1 = r;
+        bnp1 = 1f64;
+    }
+    r
+}
+
+/// Inverse of regularized incomplete beta function
+///
+/// Solves `I_x(a,b) = p` for `x`.
+pub fn invbetai(p: f64, a: f64, b: f64) -> f64 {
+    if p <= 0f64 {
+        return 0f64;
+    } else if p >= 1f64 {
+        return 1f64;
+    }
+    invbetai_pq(p, 1f64 - p, a, b)
+}
+
+/// Inverse of complementary regularized incomplete beta function
+///
+/// Solves `1 - I_x(a,b) = q` for `x` directly on the upper tail, so tiny `q` is not lost in `1 - q`.
+pub fn invbetaic(q: f64, a: f64, b: f64) -> f64 {
+    if q <= 0f64 {
+        return 1f64;
+    } else if q >= 1f64 {
+        return 0f64;
+    }
+    invbetai_pq(1f64 - q, q, a, b)
+}
+
+/// Solve `I_x(a,b) = p` or `1 - I_x(a,b) = q` (whichever is smaller) for `x`
+///
+/// Newton's method on the logarithm of the smaller tail as a function of `ln x` (or `ln(1 - x)` for `x >= 1/2`),
+/// which is nearly linear in the tails, safeguarded by bisection.
+fn invbetai_pq(p: f64, q: f64, a: f64, b: f64) -> f64 {
+    let upper = q < p;
+    let target = if upper { q } else { p };
+    let t: f64;
+    let mut x: f64;
+    let u: f64;
+    if a >= 1f64 && b >= 1f64 {
+        t = (-2f64 * target.ln()).sqrt();
+        x = (2.30753 + t * 0.27061) / (1f64 + t * (0.99229 + t * 0.04481)) - t;
+        if !upper { x = -x; }
         let al = (x.powi(2) - 3f64) / 6f64;
         let h = 2f64 / (1f64 / (2f64 * a - 1f64) + 1f64 / (2f64 * b - 1f64));
         let w = (x * (al + h).sqrt() / h) - (1f64 / (2f64 * b - 1f64) - 1f64 / (2f64 * a - 1f64)) * (al + 5f64 / 6f64 - 2f64 / (3f64 * h));
@@ -469,36 +3630,386 @@ pub fn invbetai(p: f64, a: f64, b: f64) -> f64 {
         x = if p < t / w {
             (a * w * p).powf(1f64 / a)
         } else {
-            1f64 - (b * w * (1f64 - p)).powf(1f64 / b)
+            1f64 - (b * w * q).powf(1f64 / b)
+        };
+    }
+    if !(x > 0f64 && x < 1f64) {
+        // Leading term of the tail : I_x(a,b) ~ x^a / (a B(a,b))
+        let lnbeta = ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b);
+        let e = if upper {
+            ((q.ln() + b.ln() + lnbeta) / b).exp()
+        } else {
+            ((p.ln() + a.ln() + lnbeta) / a).exp()
         };
+        // The leading term is meaningless away from the tail, so start from the middle of (0, 1)
+        x = if e >= 1f64 { 0.5 } else if upper { 1f64 - e } else { e };
     }
-    let afac = - ln_gamma(a) - ln_gamma(b) + ln_gamma(a + b);
-    for j in 0 .. 10 {
+
+    let mut lo = 0f64;
+    let mut hi = 1f64;
+    for _ in 0 .. 100 {
         if x == 0f64 || x == 1f64 {
             return x;
         }
-        let err = betai(a, b, x) - p;
-        t = (a1 * x.ln() + b1 * (1f64 - x).ln() + afac).exp();
-        u = err / t;
-        t = u / (1f64 - 0.5 * 1f64.min(u * (a1 / x - b1 / (1f64 - x))));
-        x -= t;
-        if x <= 0f64 {
-            x = 0.5 * (x + t);
+        let (pv, qv) = betai_pq(a, b, x);
+        let v = if upper { qv } else { pv };
+        let f = (v.max(0f64) / target).ln();
+        if f == 0f64 {
+            break;
+        } else if (f > 0f64) != upper {
+            hi = x;
+        } else {
+            lo = x;
         }
-        if x >= 1f64 {
-            x = 0.5 * (x + t + 1f64);
+        // Step in ln x or ln(1 - x), whichever keeps the precision of x
+        // d ln I / d ln x = x (dI/dx) / I and d ln(1 - I) / d ln x = -x (dI/dx) / (1 - I)
+        let mut slope = betai_dx(a, b, x) / v;
+        if upper {
+            slope = -slope;
         }
-        if t.abs() < EPS * x && j > 0 {
+        let (xn, du) = if x < 0.5 {
+            let du = f / (x * slope);
+            (x * (-du).exp(), du)
+        } else {
+            let y = 1f64 - x;
+            let du = -f / (y * slope);
+            (1f64 - y * (-du).exp(), du)
+        };
+        if du.abs() < 4f64 * EPS {
+            x = xn;
             break;
         }
+        // Bisection if Newton leaves the bracket
+        x = if xn > lo && xn < hi { xn } else { 0.5 * (lo + hi) };
     }
     x
 }
 
+/// Inverse of regularized incomplete beta function with respect to `a`
+///
+/// Solves `I_x(a,b) = p` for `a`. Returns `0` for `p = 1` and `+inf` for `p = 0`.
+pub fn invbetai_a(p: f64, b: f64, x: f64) -> f64 {
+    assert!(b > 0f64, "Bad b in routine invbetai_a");
+    assert!(x > 0f64 && x < 1f64, "Bad x in routine invbetai_a");
+    if p >= 1f64 {
+        return 0f64;
+    } else if p <= 0f64 {
+        return f64::INFINITY;
+    }
+    invbetai_shape(p, 1f64 - p, b, x, true)
+}
+
+/// Inverse of complementary regularized incomplete beta function with respect to `a`
+///
+/// Solves `1 - I_x(a,b) = q` for `a` without forming `1 - q`.
+pub fn invbetaic_a(q: f64, b: f64, x: f64) -> f64 {
+    assert!(b > 0f64, "Bad b in routine invbetaic_a");
+    assert!(x > 0f64 && x < 1f64, "Bad x in routine invbetaic_a");
+    if q <= 0f64 {
+        return 0f64;
+    } else if q >= 1f64 {
+        return f64::INFINITY;
+    }
+    invbetai_shape(1f64 - q, q, b, x, true)
+}
+
+/// Inverse of regularized incomplete beta function with respect to `b`
+///
+/// Solves `I_x(a,b) = p` for `b`. Returns `0` for `p = 0` and `+inf` for `p = 1`.
+pub fn invbetai_b(p: f64, a: f64, x: f64) -> f64 {
+    assert!(a > 0f64, "Bad a in routine invbetai_b");
+    assert!(x > 0f64 && x < 1f64, "Bad x in routine invbetai_b");
+    if p <= 0f64 {
+        return 0f64;
+    } else if p >= 1f64 {
+        return f64::INFINITY;
+    }
+    invbetai_shape(p, 1f64 - p, a, x, false)
+}
+
+/// Inverse of complementary regularized incomplete beta function with respect to `b`
+///
+/// Solves `1 - I_x(a,b) = q` for `b` without forming `1 - q`.
+pub fn invbetaic_b(q: f64, a: f64, x: f64) -> f64 {
+    assert!(a > 0f64, "Bad a in routine invbetaic_b");
+    assert!(x > 0f64 && x < 1f64, "Bad x in routine invbetaic_b");
+    if q >= 1f64 {
+        return 0f64;
+    } else if q <= 0f64 {
+        return f64::INFINITY;
+    }
+    invbetai_shape(1f64 - q, q, a, x, false)
+}
+
+/// Solve `I_x(a,b) = p` or `1 - I_x(a,b) = q` (whichever is smaller) for `a` (or `b`)
+///
+/// `other` is the fixed shape parameter. `I_x(a,b)` decreases with `a` and increases with `b`,
+/// so the root is bracketed from the mean `a / (a + b) = x` and refined by Brent's method on the logarithm of the smaller tail.
+fn invbetai_shape(p: f64, q: f64, other: f64, x: f64, solve_a: bool) -> f64 {
+    let upper = q < p;
+    let target = if upper { q } else { p };
+    let ln_target = target.ln();
+    let f = |s: f64| {
+        let (a, b) = if solve_a { (s, other) } else { (other, s) };
+        let (pv, qv) = betai_pq(a, b, x);
+        let v = if upper { qv } else { pv };
+        if v.is_normal() {
+            (v / target).ln()
+        } else if upper {
+            ln_betai(b, a, 1f64 - x) - ln_target
+        } else {
+            ln_betai(a, b, x) - ln_target
+        }
+    };
+    let s0 = if solve_a { other * x / (1f64 - x) } else { other * (1f64 - x) / x }.max(FPMIN);
+    monotone_root(f, s0, upper == solve_a)
+}
+
+/// Derivative of the regularized incomplete beta function with respect to `x`
+///
+/// `d/dx I_x(a,b) = x^(a-1) (1-x)^(b-1) / B(a,b)`
+pub fn betai_dx(a: f64, b: f64, x: f64) -> f64 {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betai_dx");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine betai_dx");
+    if x == 0f64 || x == 1f64 {
+        let c = if x == 0f64 { a } else { b };
+        return if c < 1f64 { f64::INFINITY } else if c == 1f64 { 1f64 / beta(a, b) } else { 0f64 };
+    }
+    betai_prefactor(a, b, x, 1f64 - x) / (x * (1f64 - x))
+}
+
+/// Derivative of the regularized incomplete beta function with respect to `a`
+pub fn betai_da(a: f64, b: f64, x: f64) -> f64 {
+    betai_dab(a, b, x).0
+}
+
+/// Derivative of the regularized incomplete beta function with respect to `b`
+pub fn betai_db(a: f64, b: f64, x: f64) -> f64 {
+    betai_dab(a, b, x).1
+}
+
+/// Derivatives of `I_x(a,b)` with respect to `a` and `b`
+///
+/// Differentiates the continued fraction of `betacf` term by term,
+/// using `I_x(a,b) = 1 - I_(1-x)(b,a)` on the same side as `betai`.
+fn betai_dab(a: f64, b: f64, x: f64) -> (f64, f64) {
+    assert!(a > 0f64 && b > 0f64, "Bad a or b in routine betai_da");
+    assert!((0f64..=1f64).contains(&x), "Bad x in routine betai_da");
+    if x == 0f64 || x == 1f64 {
+        (0f64, 0f64)
+    } else if x < (a + 1f64) / (a + b + 2f64) {
+        betacf_dab(a, b, x)
+    } else {
+        let (db, da) = betacf_dab(b, a, 1f64 - x);
+        (-da, -db)
+    }
+}
+
+/// `x^a (1-x)^b / B(a,b)`
+fn betai_prefactor(a: f64, b: f64, x: f64, y: f64) -> f64 {
+    let (m, e) = betai_prefactor_ln(a, b, x, y);
+    m * e.exp()
+}
+
+/// `(m, e)` with `x^a (1-x)^b / B(a,b) = m exp(e)`
+///
+/// For large `a` and `b` the Stirling series is used with `delta = x (a + b) - a`, so that
+/// `(x (a+b) / a)^a ((1-x) (a+b) / b)^b = exp(a ln1pmx(delta / a) + b ln1pmx(-delta / b))` does not cancel.
+fn betai_prefactor_ln(a: f64, b: f64, x: f64, y: f64) -> (f64, f64) {
+    let (s, l) = if a < b { (a, b) } else { (b, a) };
+    let (lnx, lny) = ln_xy(x, y);
+    if l < STIRLING_MIN {
+        // Powers and Gamma functions directly, which avoids exponentiating a large rounded exponent
+        let m = x.powf(a) * y.powf(b) * (gamma(a + b) / (gamma(a) * gamma(b)));
+        if m.is_normal() {
+            (m, 0f64)
+        } else {
+            (1f64, a * lnx + b * lny + ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b))
+        }
+    } else if s < STIRLING_MIN {
+        // 1 / B(a,b) = l^s exp(t) / Gamma(s)
+        let t = stirling_delta_exponent(l, s) - ln_gamma(s);
+        let e = if a < b {
+            a * (lnx + b.ln()) + b * lny
+        } else {
+            a * lnx + b * (lny + a.ln())
+        };
+        (1f64, e + t)
+    } else {
+        let c = a + b;
+        let delta = -beta_lambda(a, b, x, y);
+        let ea = if (delta / a).abs() <= 0.5 { a * ln_1p_mx(delta / a) } else { a * (x * c / a).ln() - delta };
+        let eb = if (delta / b).abs() <= 0.5 { b * ln_1p_mx(-delta / b) } else { b * (y * c / b).ln() + delta };
+        let corr = stirling_correction(c) - stirling_correction(a) - stirling_correction(b);
+        ((a * b / (2f64 * PI * c)).sqrt(), ea + eb + corr)
+    }
+}
+
+/// Derivatives of `x^a (1-x)^b / (a B(a,b)) betacf(a,b,x)` with respect to `a` and `b`
+fn betacf_dab(a: f64, b: f64, x: f64) -> (f64, f64) {
+    // d_(2m+1) = -(a+m)(a+b+m)x / ((a+2m)(a+2m+1)), d_(2m) = m(b-m)x / ((a+2m-1)(a+2m))
+    let d = |k: usize| {
+        let m = (k / 2) as f64;
+        if k % 2 == 1 {
+            let den = (a + 2f64 * m) * (a + 2f64 * m + 1f64);
+            let dk = -(a + m) * (a + b + m) * x / den;
+            let da = dk * (1f64 / (a + m) + 1f64 / (a + b + m) - 1f64 / (a + 2f64 * m) - 1f64 / (a + 2f64 * m + 1f64));
+            (dk, da, -(a + m) * x / den)
+        } else {
+            let den = (a + 2f64 * m - 1f64) * (a + 2f64 * m);
+            let dk = m * (b - m) * x / den;
+            (dk, -dk * (1f64 / (a + 2f64 * m - 1f64) + 1f64 / (a + 2f64 * m)), m * x / den)
+        }
+    };
+    let (h, dha) = cf_derivative(|n| {
+        if n == 1 {
+            (1f64, 0f64, 1f64, 0f64)
+        } else {
+            let (dk, da, _) = d(n - 1);
+            (dk, da, 1f64, 0f64)
+        }
+    });
+    let (_, dhb) = cf_derivative(|n| {
+        if n == 1 {
+            (1f64, 0f64, 1f64, 0f64)
+        } else {
+            let (dk, _, db) = d(n - 1);
+            (dk, db, 1f64, 0f64)
+        }
+    });
+    let bt = betai_prefactor(a, b, x, 1f64 - x) / a;
+    // d/da ln(bt) = ln(x) - psi(a+1) + psi(a+b), split to avoid cancellation for large a, b
+    let ab = a + b;
+    let lna = (x * ab / (a + 1f64)).ln() + ln_minus_digamma(a + 1f64) - ln_minus_digamma(ab);
+    let lnb = (-x).ln_1p() + digamma_delta(b, a);
+    (bt * (h * lna + dha), bt * (h * lnb + dhb))
+}
+
 // =============================================================================
 // Util (from Peroxide)
 // =============================================================================
+/// Polynomial `c_0 + c_1 x + ... + c_n x^n` by Horner's scheme
+fn poly(c: &[f64], x: f64) -> f64 {
+    c.iter().rev().fold(0f64, |acc, &a| acc * x + a)
+}
+
+/// Chebyshev series `c_0 / 2 + sum_(k>=1) c_k T_k(u)` by Clenshaw's recurrence
+fn chebyshev(c: &[f64], u: f64) -> f64 {
+    let u2 = 2f64 * u;
+    let mut d = 0f64;
+    let mut dd = 0f64;
+    for &cj in c[1..].iter().rev() {
+        let tmp = d;
+        d = u2 * d - dd + cj;
+        dd = tmp;
+    }
+    u * d - dd + 0.5 * c[0]
+}
+
+/// 36-point Gauss-Legendre quadrature of `f` on `[s1, s2]`
+fn gauss_legendre<F: Fn(f64) -> f64>(f: F, s1: f64, s2: f64) -> f64 {
+    let h = 0.5 * (s2 - s1);
+    let mut sum = 0f64;
+    for (y, w) in Y.iter().zip(W.iter()) {
+        sum += w * (f(s1 + h * y) + f(s2 - h * y));
+    }
+    h * sum
+}
+
+/// Root of a monotone function on `(0, inf)`
+///
+/// Brackets the root by doubling or halving `x0`, then refines it by Brent's method.
+/// Returns `0` or `+inf` if the root is beyond the range of `f64`.
+fn monotone_root<F: Fn(f64) -> f64>(f: F, x0: f64, increasing: bool) -> f64 {
+    let sign = if increasing { 1f64 } else { -1f64 };
+    let mut a = x0;
+    let mut fa = f(a);
+    if fa == 0f64 {
+        return a;
+    }
+    let step = if sign * fa < 0f64 { 2f64 } else { 0.5 };
+    loop {
+        let b = a * step;
+        if b == 0f64 || b.is_infinite() {
+            return b;
+        }
+        let fb = f(b);
+        if fb == 0f64 {
+            return b;
+        } else if fb.signum() != fa.signum() {
+            return brent(f, a, b, fa, fb);
+        }
+        a = b;
+        fa = fb;
+    }
+}
+
+/// Brent's method for a root bracketed by `a` and `b` with `f(a) = fa`, `f(b) = fb`
+///
+/// Converges to full relative precision.
+fn brent<F: Fn(f64) -> f64>(f: F, a: f64, b: f64, fa: f64, fb: f64) -> f64 {
+    let (mut a, mut b, mut c) = (a, b, b);
+    let (mut fa, mut fb, mut fc) = (fa, fb, fb);
+    let mut d = 0f64;
+    let mut e = 0f64;
+    for _ in 0 .. 200 {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tol = 2f64 * EPS * b.abs();
+        let xm = 0.5 * (c - b);
+        if xm.abs() <= tol || fb == 0f64 {
+            return b;
+        }
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // Inverse quadratic interpolation
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2f64 * xm * s, 1f64 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (s * (2f64 * xm * q * (q - r) - (b - a) * (r - 1f64)), (q - 1f64) * (r - 1f64) * (s - 1f64))
+            };
+            if p > 0f64 {
+                q = -q;
+            }
+            p = p.abs();
+            if 2f64 * p < (3f64 * xm * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                // Interpolation failed, use bisection
+                d = xm;
+                e = d;
+            }
+        } else {
+            // Bounds decreasing too slowly, use bisection
+            d = xm;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol { d } else { tol.copysign(xm) };
+        fb = f(b);
+    }
+    b
+}
+
 /// Just factorial
+///
+/// Overflows for `n > 20`. Use `checked_factorial` or `factorial_f64` for larger `n`.
 pub fn factorial(n: usize) -> usize {
     let mut p = 1usize;
     for i in 1..(n + 1) {
@@ -506,3 +4017,98 @@ pub fn factorial(n: usize) -> usize {
     }
     p
 }
+
+/// Largest `n` such that `n!` is finite in `f64`
+const MAX_FACTORIAL: usize = 170;
+
+/// Table of `n!` for `n = 0, 1, ..., 170` (correctly rounded)
+const FACTORIAL: [f64; MAX_FACTORIAL + 1] = [
+    1.0, 1.0, 2.0,
+    6.0, 24.0, 120.0,
+    720.0, 5040.0, 40320.0,
+    362880.0, 3628800.0, 39916800.0,
+    479001600.0, 6227020800.0, 87178291200.0,
+    1307674368000.0, 20922789888000.0, 355687428096000.0,
+    6402373705728000.0, 1.21645100408832e17, 2.43290200817664e18,
+    5.109094217170944e19, 1.1240007277776077e21, 2.585201673888498e22,
+    6.204484017332394e23, 1.5511210043330986e25, 4.0329146112660565e26,
+    1.0888869450418352e28, 3.0488834461171387e29, 8.841761993739702e30,
+    2.6525285981219107e32, 8.222838654177922e33, 2.631308369336935e35,
+    8.683317618811886e36, 2.9523279903960416e38, 1.0333147966386145e40,
+    3.7199332678990125e41, 1.3763753091226346e43, 5.230226174666011e44,
+    2.0397882081197444e46, 8.159152832478977e47, 3.345252661316381e49,
+    1.40500611775288e51, 6.041526306337383e52, 2.658271574788449e54,
+    1.1962222086548019e56, 5.502622159812089e57, 2.5862324151116818e59,
+    1.2413915592536073e61, 6.082818640342675e62, 3.0414093201713376e64,
+    1.5511187532873822e66, 8.065817517094388e67, 4.2748832840600255e69,
+    2.308436973392414e71, 1.2696403353658276e73, 7.109985878048635e74,
+    4.0526919504877214e76, 2.3505613312828785e78, 1.3868311854568984e80,
+    8.32098711274139e81, 5.075802138772248e83, 3.146997326038794e85,
+    1.98260831540444e87, 1.2688693218588417e89, 8.247650592082472e90,
+    5.443449390774431e92, 3.647111091818868e94, 2.4800355424368305e96,
+    1.711224524281413e98, 1.1978571669969892e100, 8.504785885678623e101,
+    6.1234458376886085e103, 4.4701154615126844e105, 3.307885441519386e107,
+    2.48091408113954e109, 1.8854947016660504e111, 1.4518309202828587e113,
+    1.1324281178206297e115, 8.946182130782976e116, 7.156945704626381e118,
+    5.797126020747368e120, 4.753643337012842e122, 3.945523969720659e124,
+    3.314240134565353e126, 2.81710411438055e128, 2.4227095383672734e130,
+    2.107757298379528e132, 1.8548264225739844e134, 1.650795516090846e136,
+    1.4857159644817615e138, 1.352001527678403e140, 1.2438414054641308e142,
+    1.1567725070816416e144, 1.087366156656743e146, 1.032997848823906e148,
+    9.916779348709496e149, 9.619275968248212e151, 9.426890448883248e153,
+    9.332621544394415e155, 9.332621544394415e157, 9.42594775983836e159,
+    9.614466715035127e161, 9.90290071648618e163, 1.0299016745145628e166,
+    1.081396758240291e168, 1.1462805637347084e170, 1.226520203196138e172,
+    1.324641819451829e174, 1.4438595832024937e176, 1.588245541522743e178,
+    1.7629525510902446e180, 1.974506857221074e182, 2.2311927486598138e184,
+    2.5435597334721877e186, 2.925093693493016e188, 3.393108684451898e190,
+    3.969937160808721e192, 4.684525849754291e194, 5.574585761207606e196,
+    6.689502913449127e198, 8.094298525273444e200, 9.875044200833601e202,
+    1.214630436702533e205, 1.506141741511141e207, 1.882677176888926e209,
+    2.372173242880047e211, 3.0126600184576594e213, 3.856204823625804e215,
+    4.974504222477287e217, 6.466855489220474e219, 8.47158069087882e221,
+    1.1182486511960043e224, 1.4872707060906857e226, 1.9929427461615188e228,
+    2.6904727073180504e230, 3.659042881952549e232, 5.012888748274992e234,
+    6.917786472619489e236, 9.615723196941089e238, 1.3462012475717526e241,
+    1.898143759076171e243, 2.695364137888163e245, 3.854370717180073e247,
+    5.5502938327393044e249, 8.047926057471992e251, 1.1749972043909107e254,
+    1.727245890454639e256, 2.5563239178728654e258, 3.80892263763057e260,
+    5.713383956445855e262, 8.62720977423324e264, 1.3113358856834524e267,
+    2.0063439050956823e269, 3.0897696138473508e271, 4.789142901463394e273,
+    7.471062926282894e275, 1.1729568794264145e278, 1.853271869493735e280,
+    2.9467022724950384e282, 4.7147236359920616e284, 7.590705053947219e286,
+    1.2296942187394494e289, 2.0044015765453026e291, 3.287218585534296e293,
+    5.423910666131589e295, 9.003691705778438e297, 1.503616514864999e300,
+    2.5260757449731984e302, 4.269068009004705e304, 7.257415615307999e306
+];
+
+/// Factorial as `f64`
+///
+/// Exact (correctly rounded) up to `170!`, and `+inf` beyond it.
+pub fn factorial_f64(n: usize) -> f64 {
+    if n <= MAX_FACTORIAL {
+        FACTORIAL[n]
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Logarithm of factorial
+pub fn ln_factorial(n: usize) -> f64 {
+    if n <= MAX_FACTORIAL {
+        FACTORIAL[n].ln()
+    } else {
+        ln_gamma(n as f64 + 1f64)
+    }
+}
+
+/// Exact factorial as `u128`
+///
+/// Returns `None` if `n!` overflows `u128` (`n > 34`).
+pub fn checked_factorial(n: usize) -> Option<u128> {
+    let mut p = 1u128;
+    for i in 2 ..= n {
+        p = p.checked_mul(i as u128)?;
+    }
+    Some(p)
+}
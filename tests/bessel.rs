@@ -0,0 +1,245 @@
+#![allow(clippy::excessive_precision)]
+
+extern crate puruspe;
+use puruspe::*;
+
+/// Assert `x` agrees with the reference `y` to relative tolerance `tol`
+fn assert_rel(x: f64, y: f64, tol: f64) {
+    assert!(((x - y) / y).abs() <= tol, "{:e} != {:e} (rel. tol. {:e})", x, y, tol);
+}
+
+// Reference values are computed with mpmath at 50 digits.
+
+#[test]
+fn j0_j1_reference() {
+    assert_rel(j0(1e-5), 0.999999999975, 1e-15);
+    assert_rel(j0(2.5), -0.048383776468197996, 1e-15);
+    // Next to the first zero
+    assert_rel(j0(2.404825557695773), -6.1087652597367304e-17, 1e-15);
+    assert_rel(j0(30f64), -0.086367983581040211, 1e-15);
+    assert_rel(j0(1e10), 2.1755917502468917e-6, 1e-15);
+    assert_rel(j1(-3f64), -0.33905895852593646, 1e-15);
+    assert_rel(j1(7.5), 0.13524842757970551, 1e-15);
+    assert_rel(j1(50f64), -0.097511828125175138, 1e-15);
+}
+
+#[test]
+fn y0_y1_reference() {
+    assert_rel(y0(1e-5), -7.4031602837019701, 1e-15);
+    assert_rel(y0(3f64), 0.37685001001279038, 1e-15);
+    assert_rel(y0(30f64), -0.11729573168666403, 1e-15);
+    assert_rel(y1(0.5), -1.4714723926702431, 1e-15);
+    assert_rel(y1(12f64), -0.057099218260896521, 1e-15);
+    assert_eq!(y0(0f64), f64::NEG_INFINITY);
+    assert!(y1(-1f64).is_nan());
+}
+
+#[test]
+fn jn_yn_reference() {
+    assert_rel(jn(2, 1f64), 0.11490348493190048, 1e-15);
+    assert_rel(jn(5, 10f64), -0.23406152818679364, 1e-15);
+    assert_rel(jn(10, 1f64), 2.6306151236874532e-10, 1e-15);
+    assert_rel(jn(50, 30f64), 2.0581656631564178e-8, 1e-15);
+    // Forward recurrence over 100 orders, where J_100 is small against sqrt(2 / (pi x))
+    assert_rel(jn(100, 150f64), -0.015359526118405391, 2e-14);
+    assert_rel(jn(3, -2f64), -0.12894324947440205, 1e-15);
+    assert_rel(yn(2, 1f64), -1.6506826068162544, 1e-15);
+    assert_rel(yn(5, 10f64), 0.1354030476893623, 1e-15);
+    assert_rel(yn(10, 1f64), -121618014.27868919, 1e-15);
+    assert_rel(yn(30, 10f64), -7256142316.1003306, 2e-15);
+    assert_rel(yn(100, 150f64), 0.073876071245019868, 1e-15);
+}
+
+#[test]
+fn bessel_jy_reference() {
+    let cases = [
+        (0.5, 1f64, 0.67139670714180309, -0.43109886801837608),
+        (0.3, 1.5, 0.63095776797879694, 0.12573091853294629),
+        (2.7, 3f64, 0.37247014563002801, -0.44027352445869235),
+        (2.7, 30f64, 0.14583053226899095, -0.0061204747642585464),
+        (10.2, 5f64, 0.001111477004267569, -32.311808027817667),
+        (40.5, 20f64, 5.0661960070771788e-10, -17844038.067349001),
+        (0.25, 1000f64, 0.024704776333357205, -0.0051277420960271934),
+        (-2.5, 4f64, -0.014567947668521801, 0.44088497455734117),
+        (150.5, 100f64, 1.6760060156073453e-16, -16886981092942.169),
+        (3.5, 1e-3, 2.4029832208058423e-13, -378469916150.01496),
+    ];
+    for &(nu, x, j, y) in cases.iter() {
+        assert_rel(bessel_j(nu, x), j, 4e-15);
+        assert_rel(bessel_y(nu, x), y, 4e-15);
+    }
+    // The reflection cancels : J_(-0.3)(2) = cos(0.3 pi) J_0.3(2) - sin(0.3 pi) Y_0.3(2)
+    assert_rel(bessel_j(-0.3, 2f64), -0.043847077073278784, 4e-14);
+    assert_rel(bessel_y(-0.3, 2f64), 0.55804356444950206, 1e-15);
+    assert_eq!(bessel_j(2f64, 3f64), jn(2, 3f64));
+    assert_eq!(bessel_y(-3f64, 3f64), -yn(3, 3f64));
+    assert!(bessel_j(0.5, -1f64).is_nan());
+}
+
+#[test]
+fn bessel_jy_tiny_argument() {
+    // (x/2)^2 below EPS (nu + 1), where only the leading term of J_nu is kept
+    let cases = [
+        (0.7, 1e-30, 6.774663949658537e-22, -6.7121913679114129e20),
+        (0.7, 1e-300, 6.7746639496587237e-211, -6.7121913679112278e209),
+        (-0.7, 1e-30, 5.4302768861371587e20, 3.9453270966231694e20),
+        (-0.7, 1e-300, 5.430276886137009e209, 3.9453270966230606e209),
+    ];
+    for &(nu, x, j, y) in cases.iter() {
+        assert_rel(bessel_j(nu, x), j, 4e-15);
+        assert_rel(bessel_y(nu, x), y, 4e-15);
+    }
+    assert_rel(bessel_j(0.3, 1e-250), 9.0504614768953497e-76, 4e-15);
+    // J_30.5(1e-30) = 4.5e-958 and Y_30.5(1e-30) = -2.3e955 are out of range
+    for &x in [1e-30, 1e-300].iter() {
+        assert_eq!(bessel_j(30.5, x), 0f64);
+        assert_eq!(bessel_y(30.5, x), f64::NEG_INFINITY);
+        assert_eq!(bessel_j(-30.5, x), f64::INFINITY);
+        assert_eq!(bessel_y(-30.5, x), 0f64);
+    }
+}
+
+#[test]
+fn bessel_ik_reference() {
+    // (nu, x, I_nu(x), K_nu(x), I_nu(x) exp(-x), K_nu(x) exp(x))
+    let cases = [
+        (0f64, 1e-3, 1.0000002500000156, 7.0236888005623813, 0.99900074958351556, 7.0307160023782515),
+        (0f64, 1f64, 1.2660658777520083, 0.42102443824070833, 0.46575960759364044, 1.144463079806895),
+        (1f64, 2.5, 2.5167162452886984, 0.073890816347747064, 0.20658464953126655, 0.90017442390787809),
+        (0.5, 1f64, 0.93767488824548765, 0.46106850444789456, 0.34495131388824463, 1.2533141373155003),
+        (2.7, 3f64, 1.2715236097070627, 0.0969221537279902, 0.063305432887838678, 1.9467334973784184),
+        (2.7, 30f64, 690850929899.26157, 2.4030878842059365e-14, 0.064647225296699234, 0.25680537591736141),
+        (10.2, 5f64, 0.0033980756105519479, 12.94579441274364, 2.2896053362784013e-5, 1921.3262458877691),
+        (40.5, 20f64, 6.3402213483755775e-8, 174585.48845274726, 1.3068170199266599e-16, 84702802620890.807),
+        (150.5, 100f64, 0.047243355506506994, 0.058571285455259582, 1.7574887184636834e-45, 1.5744647645048937e+42),
+        (3.5, 1e-3, 2.4029834878039928e-13, 594499035190.9971, 2.4005817054075355e-13, 595093831574.81364),
+        (-2.5, 4f64, 4.7717839601424545, 0.022237897617178104, 0.087398271869023131, 1.2141480705243909),
+        (0f64, 700f64, 1.5295933476718737e+302, 4.6697764316853769e-306, 0.015081295651531358, 0.047362369454613572),
+    ];
+    for &(nu, x, i, k, ie, ke) in cases.iter() {
+        assert_rel(bessel_i(nu, x), i, 2e-15);
+        assert_rel(bessel_k(nu, x), k, 2e-15);
+        assert_rel(bessel_ie(nu, x), ie, 2e-15);
+        assert_rel(bessel_ke(nu, x), ke, 2e-15);
+    }
+    // Power series with Gamma(nu + 1) taken as nu Gamma(nu)
+    assert_rel(bessel_i(31.556, 1.4733), 1.1703623658832254e-39, 2e-15);
+    // Steed's continued fraction converges slowly at x = 2
+    assert_rel(bessel_i(-0.3, 2f64), 2.2374012335988941, 3e-15);
+    assert_rel(bessel_k(-0.3, 2f64), 0.11603697434811926, 3e-15);
+    assert_rel(bessel_i(3f64, -2f64), -0.21273995923985266, 2e-15);
+    assert_rel(bessel_ie(3f64, -2f64), -0.028791222639470898, 2e-15);
+    assert!(bessel_i(0.5, -1f64).is_nan());
+    assert!(bessel_k(1f64, -1f64).is_nan());
+    assert_eq!(bessel_i(0f64, 0f64), 1f64);
+    assert_eq!(bessel_k(1f64, 0f64), f64::INFINITY);
+}
+
+#[test]
+fn bessel_ik_scaled_beyond_overflow() {
+    // I_0.25(1000) = 2.5e432 and K_0.25(1000) = 2.0e-436 are out of range, the scaled forms are not
+    assert_eq!(bessel_i(0.25, 1000f64), f64::INFINITY);
+    assert_eq!(bessel_k(0.25, 1000f64), 0f64);
+    assert_rel(bessel_ie(0.25, 1000f64), 0.012616845975937635, 1e-15);
+    assert_rel(bessel_ke(0.25, 1000f64), 0.039629559386605639, 1e-15);
+    assert_rel(bessel_ke(1f64, 800f64), 0.044332109111412112, 1e-15);
+    assert_eq!(bessel_i(2f64, f64::NEG_INFINITY), f64::INFINITY);
+    assert_eq!(bessel_k(2f64, f64::INFINITY), 0f64);
+}
+
+#[test]
+fn bessel_subnormal_argument() {
+    // x/2 and 1/x are out of range at the smallest subnormal x
+    let x = 5e-324;
+    assert_rel(bessel_i(0.3, x), 9.2215966252391466e-98, 2e-15);
+    assert_rel(bessel_k(0.3, x), 1.8073515188303354e97, 2e-15);
+    assert_rel(bessel_j(0.3, x), 9.2215966252391466e-98, 2e-15);
+    assert_rel(bessel_y(0.3, x), -1.1505957125059706e97, 2e-15);
+    assert_rel(bessel_k(0.7, x), 2.1743775086912555e226, 2e-15);
+    assert_rel(bessel_y(0.7, x), -1.3842517146242157e226, 2e-15);
+    assert_rel(bessel_i(-0.3, x), 9.3085148507228138e96, 2e-15);
+    assert_eq!(bessel_i(1.3, x), 0f64);
+    assert_eq!(bessel_k(1.3, x), f64::INFINITY);
+    assert_rel(ln_bessel_i(1.3, x), -968.82737428748323, 1e-15);
+}
+
+#[test]
+fn ln_bessel_i_reference() {
+    assert_rel(ln_bessel_i(0f64, 1000f64), 995.62730888986946, 1e-15);
+    assert_rel(ln_bessel_i(2.5, 1e5), 99993.32456873416, 1e-15);
+    assert_rel(ln_bessel_i(100f64, 1f64), -433.05161839406589, 1e-15);
+    assert_rel(ln_bessel_i(1000f64, 10f64), -4302.665291340331, 1e-15);
+    assert_rel(ln_bessel_i(0.5, 1e-300), -345.61355530175158, 1e-15);
+}
+
+#[test]
+fn spherical_jn_yn_reference() {
+    assert_rel(spherical_jn(0, 1e-3), 0.99999983333334167, 1e-15);
+    assert_rel(spherical_jn(1, 0.5), 0.16253703063606657, 1e-15);
+    assert_rel(spherical_jn(2, 1f64), 0.062035052011373861, 1e-15);
+    assert_rel(spherical_jn(5, 10f64), -0.055534511621452181, 1e-15);
+    assert_rel(spherical_jn(10, 3f64), 3.5260038931752563e-6, 1e-15);
+    assert_rel(spherical_jn(30, 10f64), 2.5120573849989429e-13, 1e-15);
+    assert_rel(spherical_jn(100, 1f64), 7.4447277416610769e-190, 1e-15);
+    assert_rel(spherical_jn(40, 25f64), 2.4527426241935805e-7, 1e-15);
+    assert_rel(spherical_jn(3, 100f64), 0.0089139973696122129, 1e-15);
+    assert_rel(spherical_yn(0, 0.5), -1.7551651237807454, 1e-15);
+    assert_rel(spherical_yn(1, 2f64), -0.35061200427605525, 1e-15);
+    assert_rel(spherical_yn(5, 10f64), 0.093833541678691808, 1e-15);
+    assert_rel(spherical_yn(10, 3f64), -4699.8591888113912, 1e-15);
+    assert_rel(spherical_yn(3, 100f64), -0.0045387989509391743, 1e-15);
+    assert_eq!(spherical_jn_array(4, f64::INFINITY), vec![0f64; 5]);
+}
+
+#[test]
+fn spherical_in_kn_reference() {
+    let cases = [
+        (0, 0.5, 1.0421906109874947),
+        (1, 0.5, 0.17087070843777212),
+        (2, 1f64, 0.071562870129474492),
+        (3, 5f64, 4.1575359353958779),
+        (5, 2f64, 0.0035848483012706553),
+        (10, 30f64, 28144334181.074596),
+        (20, 1e-2, 7.6259878723145197e-66),
+        (50, 10f64, 5.889916154050247e-31),
+        (0, 700f64, 7.2445146766786036e+300),
+        (200, 100f64, 8.4840315915516357e-32),
+        (1200, 700f64, 1.1804510921384161e-82),
+        (2000, 1400f64, 4.6022194353116758e+53),
+    ];
+    for &(n, x, i) in cases.iter() {
+        assert_rel(spherical_in(n, x), i, 1e-15);
+    }
+    assert_rel(spherical_in(1, -3f64), -2.2427901177692662, 1e-15);
+    assert_rel(spherical_kn(0, 0.5), 1.9054722647301799, 1e-15);
+    assert_rel(spherical_kn(1, 2f64), 0.15943812434536362, 1e-15);
+    assert_rel(spherical_kn(5, 10f64), 2.9052984510365131e-5, 1e-15);
+    assert_rel(spherical_kn(10, 3f64), 4595.8396399769944, 1e-15);
+    assert_rel(spherical_kn(3, 700f64), 2.2315384083798714e-307, 1e-15);
+    assert!(spherical_kn(1, -1f64).is_nan());
+}
+
+#[test]
+fn spherical_in_beyond_overflow_of_i0() {
+    // i_0(717.7) overflows, i_31(717.7) does not
+    assert_rel(spherical_in(31, 717.7), 1.7213780122470631e+308, 1e-15);
+    assert_eq!(spherical_in(0, 1e300), f64::INFINITY);
+    assert_eq!(spherical_in(3, 1e20), f64::INFINITY);
+    assert_eq!(spherical_in(0, f64::INFINITY), f64::INFINITY);
+    assert_eq!(spherical_in(1, f64::INFINITY), f64::INFINITY);
+    assert_eq!(spherical_in(3, f64::NEG_INFINITY), f64::NEG_INFINITY);
+    assert_eq!(spherical_in_array(2, f64::NEG_INFINITY), vec![f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY]);
+}
+
+#[test]
+fn riccati_reference() {
+    assert_rel(riccati_psi(2, 1.5), 0.19102392553261232, 1e-15);
+    let xi = riccati_xi(2, 1.5);
+    assert_rel(xi.re, 0.19102392553261232, 1e-15);
+    assert_rel(xi.im, -2.0185690404306765, 1e-15);
+    let psi = riccati_psi_array(5, 10f64);
+    let xi = riccati_xi_array(5, 10f64);
+    assert_rel(psi[5], -0.55534511621452181, 1e-15);
+    assert_rel(xi[5].re, -0.55534511621452181, 1e-15);
+    assert_rel(xi[5].im, 0.93833541678691808, 1e-15);
+}
@@ -0,0 +1,124 @@
+#![allow(clippy::excessive_precision)]
+
+extern crate puruspe;
+use puruspe::*;
+
+/// Assert `x` agrees with the reference `y` to relative tolerance `tol`
+fn assert_rel(x: f64, y: f64, tol: f64) {
+    assert!(((x - y) / y).abs() <= tol, "{:e} != {:e} (rel. tol. {:e})", x, y, tol);
+}
+
+// Reference values are computed with mpmath at 50 digits.
+
+#[test]
+fn betai_derivative_reference() {
+    // (a, b, x, dI/da, dI/db, dI/dx)
+    let cases = [
+        (2f64, 3f64, 0.4, -0.24086937608755657, 0.15634433641359878, 1.728),
+        (0.5, 0.5, 0.2, -0.64197567826920871, 0.38756376927544048, 0.79577471545947666),
+        (10f64, 5f64, 0.7, -0.069737428827630126, 0.15297439889794808, 3.2719108091670001),
+        (50f64, 40f64, 0.55, -0.037748808936141434, 0.046698562755173058, 7.5346512980173058),
+        (0.3, 4f64, 0.9, -6.2204620047748311e-5, 3.2273411821301585e-5, 0.00053111109736296614),
+    ];
+    for &(a, b, x, da, db, dx) in cases.iter() {
+        // The shape derivatives carry the digamma cancellation of d/da ln B(a,b)
+        assert_rel(betai_da(a, b, x), da, 1e-14);
+        assert_rel(betai_db(a, b, x), db, 1e-14);
+        assert_rel(betai_dx(a, b, x), dx, 1e-15);
+    }
+}
+
+#[test]
+fn invbetai_shape_reference() {
+    assert_rel(invbetai_a(0.3, 2f64, 0.4), 2.2450575619523468, 1e-15);
+    assert_rel(invbetai_a(0.999, 10f64, 0.7), 4.4327686140666471, 1e-15);
+    assert_rel(invbetaic_a(1e-20, 3f64, 0.2), 2.0431600711656016e-20, 1e-15);
+    assert_rel(invbetai_b(1e-50, 5f64, 0.5), 9.2119084350227333e-49, 1e-15);
+    assert_rel(invbetaic_b(0.7, 0.5, 0.9), 0.10203933026583591, 1e-15);
+    // Subnormal targets, where the tail is only available as a logarithm
+    assert_rel(invbetai_a(1e-315, 2f64, 0.5), 1055.453728712703, 1e-15);
+    assert_rel(invbetaic_b(1e-315, 3f64, 0.5), 1063.5233988930337, 1e-15);
+    assert_eq!(invbetai_a(1f64, 2f64, 0.5), 0f64);
+    assert_eq!(invbetai_b(1f64, 2f64, 0.5), f64::INFINITY);
+}
+
+#[test]
+fn betaic_reference() {
+    assert_rel(betaic(2f64, 3f64, 0.9), 0.0036999999999999976, 1e-15);
+    assert_rel(betaic(5f64, 0.5, 0.1), 0.99999742941030077, 1e-15);
+    assert_rel(betaic(10f64, 10f64, 0.95), 5.9393390596644296e-9, 1e-15);
+    assert_rel(betaic(0.5, 7.5, 0.99), 2.0351021496905947e-16, 1e-14);
+}
+
+#[test]
+fn invbetaic_reference() {
+    // Roots near 1 are compared through 1 - x, to within an ULP of x
+    let tol = f64::EPSILON / 2f64;
+    assert!((1f64 - invbetaic(1e-20, 2f64, 3f64) - 1.3572088543478517e-7).abs() <= tol);
+    assert!((1f64 - invbetaic(1e-50, 10f64, 10f64) - 3.18745657707187e-6).abs() <= tol);
+    assert!((1f64 - invbetaic(0.3, 0.5, 0.5) - 0.20610737385376342).abs() <= tol);
+}
+
+#[test]
+fn invbetai_start_outside_unit_interval() {
+    // The tail approximation of the starting value exceeds 1 for small b
+    let tol = f64::EPSILON / 2f64;
+    assert!((1f64 - invbetai(0.4, 1.5, 0.015) - 8.8275885980690303e-16).abs() <= tol);
+    assert!((1f64 - invbetai(0.5, 1.5, 0.02) - 4.8418398627713424e-16).abs() <= tol);
+    // 1 - x = 3.1e-18 rounds to x = 1
+    assert_eq!(invbetai(0.4129, 12.34, 0.01429), 1f64);
+}
+
+#[test]
+fn betai_one_large_parameter() {
+    // (a, b, x, I_x(a,b), 1 - I_x(a,b))
+    let cases = [
+        (1e6, 2f64, 0.999998, 0.40600503771235185, 0.59399496228764815),
+        (2f64, 1e6, 2e-6, 0.59399496230213199, 0.40600503769786801),
+        (1e5, 0.5, 0.99999, 0.15729868816242578, 0.84270131183757422),
+        (5000f64, 3000f64, 0.62, 0.17773206624208331, 0.82226793375791669),
+        (0.3, 500f64, 0.001, 0.81377889631575804, 0.18622110368424196),
+        (200f64, 3.5, 0.97, 0.092341705902869979, 0.90765829409713002),
+        (1e4, 25f64, 0.998, 0.84076194818848197, 0.15923805181151803),
+    ];
+    for &(a, b, x, p, q) in cases.iter() {
+        assert_rel(betai(a, b, x), p, 2e-15);
+        assert_rel(betaic(a, b, x), q, 2e-15);
+    }
+    // Far tails, where the first terms of bup underflow before the sum does.
+    // The exponent near -700 limits the relative accuracy to about 1e-13.
+    assert_rel(betaic(34f64, 800f64, 0.6), 3.1822712306196259e-267, 2e-13);
+    assert_rel(betaic(14.5, 2400f64, 0.27), 3.9082400245252444e-301, 2e-13);
+}
+
+#[test]
+fn ln_beta_reference() {
+    assert_rel(ln_beta(1e6, 2e6), -1909548.290968533, 1e-15);
+    assert_rel(ln_beta(0.5, 0.25), 1.6571065161914822, 1e-15);
+    assert_rel(ln_beta(3f64, 4f64), -4.0943445622221007, 1e-15);
+}
+
+#[test]
+fn incbeta_ln_betai_reference() {
+    assert_rel(incbeta(2.5, 1.5, 0.3), 0.017464059205992956, 1e-15);
+    assert_rel(incbeta(0.5, 0.5, 0.9), 2.4980915447965089, 1e-15);
+    // ln I_x(a,b) where I_x(a,b) underflows
+    assert_rel(ln_betai(2f64, 3f64, 1e-200), -919.24227772839022, 1e-15);
+    assert_rel(ln_betai(500f64, 500f64, 0.1), -514.97610698075692, 1e-15);
+    assert_rel(ln_betai(50f64, 2f64, 0.5), -31.399262489975783, 1e-15);
+    assert_rel(ln_betai(0.5, 0.5, 0.5), -std::f64::consts::LN_2, 1e-15);
+}
+
+#[test]
+fn betai_diff_reference() {
+    assert_rel(betai_diff(2f64, 3f64, 0.4, 0.4001), 0.00017279279680028097, 1e-15);
+    assert_rel(betai_diff(10f64, 10f64, 0.9, 0.91), 2.4322117752506916e-6, 1e-14);
+    assert_rel(betai_diff(0.5, 0.5, 0.001, 0.002), 0.0083449588218875714, 1e-15);
+    assert_rel(betai_diff(3f64, 2f64, 0.2, 0.7), 0.62449999999999992, 1e-15);
+}
+
+#[test]
+fn betai_small_parameter_prefactor() {
+    // x^a (1-x)^b / B(a,b) = exp(-23.3), whose rounded exponent alone would cost several ULP
+    assert_rel(betaic(6.5, 7f64, 0.99), 1.1233058683665526e-11, 1e-15);
+}
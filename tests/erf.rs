@@ -0,0 +1,202 @@
+#![allow(clippy::excessive_precision)]
+
+extern crate puruspe;
+use puruspe::*;
+
+/// Assert `x` agrees with the reference `y` to relative tolerance `tol`
+fn assert_rel(x: f64, y: f64, tol: f64) {
+    assert!(((x - y) / y).abs() <= tol, "{:e} != {:e} (rel. tol. {:e})", x, y, tol);
+}
+
+// Reference values are computed with mpmath at 50 digits.
+
+#[test]
+fn erfcx_reference() {
+    assert_rel(erfcx(0.3), 0.73459933456765515, 1e-15);
+    assert_rel(erfcx(5f64), 0.11070463773306863, 1e-15);
+    assert_rel(erfcx(12.5), 0.044992099001027921, 1e-15);
+    assert_rel(erfcx(1e5), 5.6418958351954681e-6, 1e-15);
+    assert_rel(erfcx(1e200), 5.6418958354775628e-201, 1e-15);
+    // 1 / (x sqrt(pi)) beyond the continued fraction, up to f64::MAX
+    assert_rel(erfcx(2e8), 2.8209479177387814e-9, 1e-15);
+    assert_rel(erfcx(f64::MAX), 3.1384087339854432e-309, 1e-15);
+    // 2 exp(x^2) - erfcx(-x), with the rounding of x^2 compensated
+    assert_rel(erfcx(-0.5), 1.9523604891825571, 1e-15);
+    assert_rel(erfcx(-3.7), 1764092.7560908963, 1e-15);
+    assert_rel(erfcx(-10.123), 6.3887082031554001e+44, 1e-15);
+    assert_rel(erfcx(-23.38), 4.9772629970765612e+237, 1e-15);
+    assert_rel(erfcx(-26.6), 3.894337719605585e+307, 1e-15);
+}
+
+#[test]
+fn ln_erfc_reference() {
+    assert_rel(ln_erfc(-3f64), 0.69313613525044681, 1e-15);
+    assert_rel(ln_erfc(0.5), -0.7350111298370844, 1e-15);
+    assert_rel(ln_erfc(30f64), -903.97411711064388, 1e-15);
+    assert_rel(ln_erfc(1e3), -1000007.4801207219, 1e-15);
+    assert_rel(ln_erfc(1e10), -1e20, 1e-15);
+}
+
+/// Assert `z` agrees with `re + i im` to relative tolerance `tol` in modulus
+fn assert_complex(z: Complex, re: f64, im: f64, tol: f64) {
+    let d = (z.re - re).hypot(z.im - im);
+    assert!(d <= tol * re.hypot(im), "{:?} != {:e} + {:e}i (rel. tol. {:e})", z, re, im, tol);
+}
+
+#[test]
+fn faddeeva_reference() {
+    assert_complex(faddeeva(Complex::new(1f64, 1f64)), 0.30474420525691259, 0.20821893820283163, 2e-15);
+    assert_complex(faddeeva(Complex::new(0.5, 0.01)), 0.77234501841006655, 0.47121688569118492, 2e-15);
+    assert_complex(faddeeva(Complex::new(3f64, -2f64)), -0.08133907992862736, 0.12108616246299845, 2e-15);
+    assert_complex(faddeeva(Complex::new(1e-3, 1e-3)), 0.99887162233541125, 0.0011263806715998665, 2e-15);
+    assert_complex(faddeeva(Complex::new(20f64, 5f64)), 0.0066592212632078247, 0.02657402237908979, 2e-15);
+    // i / (sqrt(pi) z) far from the origin
+    assert_complex(faddeeva(Complex::new(1e300, 1e300)), 2.8209479177387814e-301, 2.8209479177387814e-301, 2e-15);
+    assert_complex(faddeeva(Complex::new(2e8, 3e8)), 1.3019759620332837e-9, 8.6798397468885582e-10, 2e-15);
+}
+
+#[test]
+fn erf_complex_reference() {
+    assert_complex(erfc_complex(Complex::new(1f64, 2f64)), 1.536643565778565, 5.0491437034470347, 2e-15);
+    assert_complex(erfc_complex(Complex::new(-0.3, 0.2)), 1.3412374814721386, -0.20852883788276888, 2e-15);
+    assert_complex(erfc_complex(Complex::new(3f64, -1f64)), 5.7613867986237604e-5, 7.7179563813780136e-7, 2e-15);
+    assert_complex(erf_complex(Complex::new(2f64, 1f64)), 1.0036063427256518, -0.011259006028815025, 2e-15);
+    // Small real part near the imaginary axis, where 1 - erfc(z) cancels
+    let z = erf_complex(Complex::new(1e-300, 2f64));
+    assert_rel(z.re, 6.160741505935513e-299, 2e-15);
+    assert_rel(z.im, 18.564802414575553, 2e-15);
+    let z = erf_complex(Complex::new(1e-10, 3f64));
+    assert_rel(z.re, 9.1433510931025469e-7, 2e-15);
+    assert_rel(z.im, 1629.9946226015657, 2e-15);
+    // Overflow keeps the signs of the components
+    let inf = f64::INFINITY;
+    assert_eq!(erf_complex(Complex::new(1f64, 30f64)), Complex::new(-inf, -inf));
+    assert_eq!(erf_complex(Complex::new(1.8, 1926f64)), Complex::new(-inf, -inf));
+    assert_eq!(erf_complex(Complex::new(-1f64, 30f64)), Complex::new(inf, -inf));
+    assert_eq!(erfc_complex(Complex::new(-30f64, 1f64)).re, 2f64);
+}
+
+#[test]
+fn voigt_profile_reference() {
+    assert_rel(voigt_profile(0.5, 1f64, 0.3), 0.28900274222650461, 2e-15);
+    assert_rel(voigt_profile(10f64, 0.2, 1.5), 0.0046749119059284145, 2e-15);
+}
+
+#[test]
+fn dawson_reference() {
+    assert_rel(dawson(1e-10), 1e-10, 1e-15);
+    assert_rel(dawson(0.5), 0.4244363835020223, 1e-15);
+    assert_rel(dawson(1f64), 0.53807950691276842, 1e-15);
+    assert_rel(dawson(2.5), 0.22308372216743548, 1e-15);
+    assert_rel(dawson(4f64), 0.12934800123600512, 1e-15);
+    assert_rel(dawson(10f64), 0.050253847187598528, 1e-15);
+    assert_rel(dawson(1e8), 5.0000000000000002e-9, 1e-15);
+    assert_rel(dawson(-3f64), -0.17827103061055829, 1e-15);
+}
+
+#[test]
+fn erfi_reference() {
+    assert_rel(erfi(1e-10), 1.1283791670955126e-10, 1e-15);
+    assert_rel(erfi(0.5), 0.61495209469651098, 1e-15);
+    assert_rel(erfi(2f64), 18.564802414575553, 1e-15);
+    assert_rel(erfi(5f64), 8298273880.6768035, 1e-15);
+    assert_rel(erfi(26f64), 8.3146371647309877e+291, 1e-15);
+    assert_rel(erfi(-1.5), -4.5847332572844269, 1e-15);
+    assert_eq!(erfi(30f64), f64::INFINITY);
+    assert_eq!(erfi(f64::NEG_INFINITY), f64::NEG_INFINITY);
+}
+
+#[test]
+fn fresnel_reference() {
+    let cases = [
+        (1e-5, 5.2359877559829887e-16, 1e-5),
+        (0.5, 0.064732432859999278, 0.49234422587144639),
+        (3f64, 0.49631299896737504, 0.60572078929768563),
+        (10.5, 0.52804040799812976, 0.48848000730270921),
+        (123.456, 0.50146234679082449, 0.50212351355041566),
+        (1e4, 0.49996816901138162, 0.49999999999989868),
+        (100000000.3, 0.49999999777068784, 0.49999999772793363),
+        (-2f64, -0.34341567836369824, -0.48825340607534075),
+    ];
+    for &(x, s, c) in cases.iter() {
+        assert_rel(fresnel_s(x), s, 1e-15);
+        assert_rel(fresnel_c(x), c, 1e-15);
+        assert_eq!(fresnel(x), (fresnel_s(x), fresnel_c(x)));
+    }
+    // Both the series and the continued fraction lose a few ulps near the switch at 1.5
+    assert_rel(fresnel_s(1.4), 0.71352507736341211, 2e-15);
+    assert_rel(fresnel_c(1.4), 0.54309578354625639, 2e-15);
+    assert_rel(fresnel_s(1.6), 0.6388876835093809, 2e-15);
+    assert_rel(fresnel_c(1.6), 0.36546168344048771, 2e-15);
+    assert_eq!(fresnel(f64::INFINITY), (0.5, 0.5));
+    assert_eq!(fresnel(f64::NEG_INFINITY), (-0.5, -0.5));
+}
+
+#[test]
+fn norm_cdf_reference() {
+    assert_eq!(norm_cdf(0f64), 0.5);
+    assert_rel(norm_cdf(1.5), 0.93319279873114193, 1e-15);
+    assert_rel(norm_sf(1.5), 0.066807201268858066, 1e-15);
+    assert_rel(norm_cdf(-3f64), 0.0013498980316300945, 1e-15);
+    assert_rel(norm_sf(-3f64), 0.99865010196836991, 1e-15);
+    assert_rel(norm_cdf(-20f64), 2.7536241186062337e-89, 1e-15);
+    assert_rel(norm_cdf(-37f64), 5.7255712225245768e-300, 1e-15);
+    assert_rel(norm_sf(8f64), 6.2209605742717841e-16, 1e-15);
+    assert_eq!(norm_sf(40f64), 0f64);
+    assert_eq!(norm_cdf(40f64), 1f64);
+}
+
+#[test]
+fn norm_logcdf_reference() {
+    assert_rel(norm_logcdf(0f64), -std::f64::consts::LN_2, 1e-15);
+    assert_rel(norm_logcdf(-3f64), -6.6077262215103495, 1e-15);
+    assert_rel(norm_logcdf(-40f64), -804.60844201375379, 1e-15);
+    assert_rel(norm_logcdf(-1e3), -500007.82669481218, 1e-15);
+    assert_rel(norm_logcdf(-1e4), -50000010.129278915, 1e-15);
+    assert_rel(norm_logcdf(5f64), -2.8665161296376359e-7, 1e-15);
+    assert_eq!(norm_logcdf(f64::NEG_INFINITY), f64::NEG_INFINITY);
+}
+
+#[test]
+fn norm_ppf_reference() {
+    assert_eq!(norm_ppf(0.5), 0f64);
+    assert_rel(norm_ppf(0.5 + 1e-10), 2.5066284820303539e-10, 1e-15);
+    assert_rel(norm_ppf(0.3), -0.52440051270804082, 1e-15);
+    assert_rel(norm_ppf(0.01), -2.3263478740408411, 1e-15);
+    assert_rel(norm_ppf(0.975), 1.9599639845400539, 1e-15);
+    assert_rel(norm_ppf(0.999999), 4.7534243088170878, 1e-15);
+    assert_rel(norm_ppf(1e-20), -9.2623400897984076, 1e-15);
+    assert_rel(norm_ppf(1e-300), -37.047096299361199, 1e-15);
+    assert_eq!(norm_ppf(0f64), f64::NEG_INFINITY);
+    assert_eq!(norm_ppf(1f64), f64::INFINITY);
+}
+
+#[test]
+fn inverf_reference() {
+    assert_rel(inverf(1e-20), 8.8622692545275797e-21, 1e-15);
+    assert_rel(inverf(0.3), 0.27246271472675435, 1e-15);
+    assert_rel(inverf(0.84), 0.99353562834730419, 1e-15);
+    assert_rel(inverf(0.9), 1.1630871536766742, 1e-15);
+    assert_rel(inverf(-0.999), -2.3267537655135245, 1e-15);
+    assert_rel(inverf(0.999999999), 4.320005388105362, 1e-15);
+    // Subnormal arguments are correctly rounded
+    assert_eq!(inverf(1e-310), 8.8622692545277e-311);
+    assert_eq!(inverf(5e-324), 5e-324);
+    assert_eq!(inverf(0f64), 0f64);
+    assert_eq!(inverf(1f64), f64::INFINITY);
+    assert_eq!(inverf(-1f64), f64::NEG_INFINITY);
+}
+
+#[test]
+fn inverfc_reference() {
+    assert_rel(inverfc(0.5), 0.47693627620446987, 1e-15);
+    assert_rel(inverfc(1.3), -0.2724627147267544, 1e-15);
+    assert_rel(inverfc(1.999), -2.3267537655135466, 1e-15);
+    assert_rel(inverfc(0.01), 1.8213863677184497, 1e-15);
+    assert_rel(inverfc(1e-20), 6.6015806223551426, 1e-15);
+    assert_rel(inverfc(1e-300), 26.209469960516124, 1e-15);
+    assert_rel(inverfc(5e-324), 27.213293210812949, 1e-15);
+    assert_eq!(inverfc(1f64), 0f64);
+    assert_eq!(inverfc(0f64), f64::INFINITY);
+    assert_eq!(inverfc(2f64), f64::NEG_INFINITY);
+}
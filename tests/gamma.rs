@@ -0,0 +1,355 @@
+#![allow(clippy::excessive_precision)]
+
+extern crate puruspe;
+use puruspe::*;
+
+/// Assert `x` agrees with the reference `y` to relative tolerance `tol`
+fn assert_rel(x: f64, y: f64, tol: f64) {
+    assert!(((x - y) / y).abs() <= tol, "{:e} != {:e} (rel. tol. {:e})", x, y, tol);
+}
+
+/// Assert `x` is within `n` units in the last place of the reference `y`
+fn assert_ulp(x: f64, y: f64, n: f64) {
+    let ulp = f64::from_bits(y.abs().to_bits() + 1) - y.abs();
+    assert!((x - y).abs() <= n * ulp, "{:e} != {:e} ({} ULP)", x, y, n);
+}
+
+// Reference values are computed with mpmath at 50 digits.
+
+#[test]
+fn digamma_reference() {
+    assert_rel(digamma(0.5), -1.9635100260214235, 2e-15);
+    assert_rel(digamma(1f64), -0.57721566490153286, 2e-15);
+    assert_rel(digamma(2.5), 0.70315664064524319, 2e-15);
+    assert_rel(digamma(100f64), 4.6001618527380874, 2e-15);
+    assert_rel(digamma(1e-5), -100000.57719921568, 2e-15);
+    assert_rel(digamma(1e10), 23.025850929890457, 2e-15);
+    // Reflection for negative arguments
+    assert_rel(digamma(-0.5), 0.036489973978576521, 2e-15);
+    assert_rel(digamma(-2.7), -1.115347129140687, 1e-14);
+    assert_rel(digamma(-10.3), 4.6624034935820977, 1e-14);
+    // Near the positive root x0 = 1.4616321449683623...
+    assert_rel(digamma(1.5), 0.036489973978576521, 2e-15);
+    assert_rel(digamma(1.46), -0.0015805619870834521, 2e-15);
+    assert_rel(digamma(1.4616321449683622), -9.2412655217294275e-17, 1e-9);
+    assert!(digamma(0f64).is_nan());
+    assert!(digamma(-3f64).is_nan());
+}
+
+#[test]
+fn trigamma_reference() {
+    assert_rel(trigamma(0.5), 4.9348022005446793, 2e-15);
+    assert_rel(trigamma(1f64), 1.6449340668482264, 2e-15);
+    assert_rel(trigamma(3.7), 0.31003785767003832, 2e-15);
+    assert_rel(trigamma(50f64), 0.020201333226697126, 2e-15);
+    assert_rel(trigamma(1e-4), 100000001.64469369, 2e-15);
+    assert_rel(trigamma(-1.5), 9.3792466449891238, 1e-14);
+    assert_rel(trigamma(-4.2), 28.354875744597707, 1e-14);
+    assert_eq!(trigamma(-2f64), f64::INFINITY);
+}
+
+#[test]
+fn polygamma_reference() {
+    assert_eq!(polygamma(0, 2.5), digamma(2.5));
+    assert_rel(polygamma(2, 0.5), -16.82879664423432, 2e-15);
+    assert_rel(polygamma(3, 2f64), 0.49393940226682915, 2e-15);
+    assert_rel(polygamma(5, 10f64), 0.00030594516211726821, 2e-15);
+    assert_rel(polygamma(10, 3.3), -7.6168055358183719, 2e-15);
+    assert_rel(polygamma(4, -1.5), -0.31375599950673136, 1e-14);
+    assert_rel(polygamma(2, -0.25), 122.69736678366236, 1e-14);
+}
+
+#[test]
+fn ln_gamma_signed_reference() {
+    let check = |z: f64, value: f64, sign: i8, tol: f64| {
+        let (v, s) = ln_gamma_signed(z);
+        assert_eq!(s, sign, "sign of Gamma({})", z);
+        assert_rel(v, value, tol);
+    };
+    check(3.5, 1.2009736023470742, 1, 2e-15);
+    check(200.5, 860.58220350978249, 1, 2e-15);
+    check(-0.5, 1.2655121234846454, -1, 2e-15);
+    check(-2.5, -0.056243716497674051, -1, 4e-15);
+    check(-1e-8, 18.420680749724522, -1, 2e-15);
+    check(-0.9999999, 16.118095693763122, -1, 2e-15);
+    check(-5.000001, 9.0280171089263357, 1, 2e-15);
+    check(-170.3, -706.75828179764708, -1, 2e-15);
+    assert_eq!(ln_gamma_signed(-4f64), (f64::INFINITY, 1));
+    assert_eq!(ln_gamma_signed(0f64), (f64::INFINITY, 1));
+}
+
+#[test]
+fn ln_gamma_ulp() {
+    // Dense around the minimum of Gamma at 1.4616..., where ln Gamma(z + 1) - ln(z) cancels
+    let cases = [
+        (1.2, -0.085374090003315837),
+        (1.25, -0.098271836421813161),
+        (1.3, -0.10817480950786048),
+        (1.35, -0.11524089735244514),
+        (1.4, -0.11961291417237129),
+        (1.4384, -0.12122327875218263),
+        (1.4616321449683622, -0.12148629053584961),
+        (1.47, -0.12145249800765601),
+        (1.4969086157822935, -0.12089057128693161),
+        (1.498, -0.12085334687279877),
+        (0.6, 0.39823385806923493),
+        (3.7, 1.4280723266653881),
+        (25.5, 56.389167643719947),
+        (1e5, 1051287.7089736569),
+        (1e300, 6.8977552789821374e302),
+    ];
+    for &(z, v) in cases.iter() {
+        assert_ulp(ln_gamma(z), v, 3f64);
+    }
+    assert_eq!(ln_gamma(1f64), 0f64);
+    assert_eq!(ln_gamma(2f64), 0f64);
+}
+
+#[test]
+fn gamma_ulp() {
+    let cases = [
+        (0.5, 1.772453850905516),
+        (1.5, 0.88622692545275801),
+        (2.5, 1.329340388179137),
+        (7.3, 1271.4236336639088),
+        (51.82341269466261, 7.7355651171492096e65),
+        (146.5622219875355, 1.3248439453272026e253),
+        (171.5, 9.4833675668247993e307),
+        (1e-10, 9999999999.422784),
+        (-0.5, -3.5449077018110321),
+        (-1.5, 2.3632718012073547),
+        (-22.056843002859267, -1.3181712247374393e-20),
+        (-54.88684381736732, -1.1198378449563662e-72),
+        (-153.4355503829468, 1.7835256344825266e-270),
+    ];
+    for &(z, v) in cases.iter() {
+        assert_ulp(gamma(z), v, 6f64);
+    }
+    assert_eq!(gamma(24f64), 25852016738884976640000f64);
+    assert_eq!(gamma(172f64), f64::INFINITY);
+}
+
+#[test]
+fn factorial_table() {
+    assert_eq!(factorial(20), 2432902008176640000);
+    assert_eq!(factorial_f64(20), 2432902008176640000f64);
+    // Correctly rounded values of n!
+    assert_eq!(factorial_f64(50), 3.0414093201713376e64);
+    assert_eq!(factorial_f64(100), 9.332621544394415e157);
+    assert_eq!(factorial_f64(170), 7.257415615307999e306);
+    assert_eq!(factorial_f64(171), f64::INFINITY);
+    for n in 1 .. 171 {
+        assert_eq!(gamma(n as f64), factorial_f64(n - 1));
+    }
+}
+
+#[test]
+fn ln_factorial_reference() {
+    assert_eq!(ln_factorial(0), 0f64);
+    assert_ulp(ln_factorial(10), 15.104412573075515, 2f64);
+    assert_ulp(ln_factorial(100), 363.73937555556349, 2f64);
+    assert_ulp(ln_factorial(171), 711.71472580229001, 2f64);
+    assert_ulp(ln_factorial(1000), 5912.1281784881633, 2f64);
+    assert_ulp(ln_factorial(100000), 1051299.2218991219, 2f64);
+}
+
+#[test]
+fn checked_factorial_range() {
+    assert_eq!(checked_factorial(0), Some(1));
+    assert_eq!(checked_factorial(34), Some(295232799039604140847618609643520000000));
+    assert_eq!(checked_factorial(35), None);
+}
+
+#[test]
+fn binomial_reference() {
+    assert_eq!(binomial(10, 3), 120f64);
+    assert_eq!(binomial(3, 10), 0f64);
+    assert_eq!(binomial(100, 50), 100891344545564193334812497256f64);
+    // Beyond u128
+    assert_ulp(binomial(1000, 500), 2.7028824094543657e299, 1f64);
+    assert_ulp(binomial(600, 300), 1.3510794199619427e179, 1f64);
+    assert_ulp(binomial(400, 180), 1.3956393658548692e118, 1f64);
+    assert_ulp(binomial(1029, 514), 1.4298206864989041e308, 1f64);
+    assert_eq!(binomial(1030, 515), f64::INFINITY);
+    assert_ulp(binomial(1000000000000000, 20), 4.1103176233113841e281, 1f64);
+}
+
+#[test]
+fn binomial_real_reference() {
+    assert_eq!(binomial_real(10.5, 3f64), 141.3125);
+    assert_eq!(binomial_real(-2.5, 4f64), 9.0234375);
+    assert_eq!(binomial_real(-7f64, 3f64), -84f64);
+    assert_eq!(binomial_real(5f64, -1f64), 0f64);
+    assert_eq!(binomial_real(1000f64, 500f64), binomial(1000, 500));
+    assert_rel(binomial_real(7.3, 2.6), 34.4281969556244, 2e-15);
+    assert!(binomial_real(-3f64, 0.5).is_nan());
+    assert_rel(ln_binomial(1e5, 5e4), 69308.735799409401, 2e-15);
+    assert_rel(ln_binomial(2.5, 1.5), 0.91629073187415507, 2e-15);
+}
+
+#[test]
+fn pochhammer_reference() {
+    assert_eq!(pochhammer(3.5, 4f64), 563.0625);
+    assert_eq!(pochhammer(-3f64, 2f64), 6f64);
+    assert_eq!(pochhammer(-3f64, 5f64), 0f64);
+    assert_eq!(pochhammer(-2.5, 3f64), -1.875);
+    assert_rel(pochhammer(2.5, -1f64), 0.66666666666666667, 2e-16);
+    assert_rel(pochhammer(0.5, 2.25), 0.90741963248513502, 2e-15);
+    assert_rel(pochhammer(1e10, 0.5), 99999.99999875, 2e-15);
+    assert_eq!(rising_factorial(2.5, 3), 39.375);
+    assert_eq!(falling_factorial(5.5, 3), 86.625);
+    assert_eq!(falling_factorial(-1.5, 4), 59.0625);
+}
+
+#[test]
+fn gamma_delta_ratio_reference() {
+    // Large a with small delta, where Gamma(a + delta) / Gamma(a) rounds a + delta
+    assert_rel(gamma_delta_ratio(160.3, 1e-10), 1.0000000005073925, 4e-16);
+    assert_rel(gamma_delta_ratio(169f64, 0.7), 36.245239354346478, 4e-16);
+    assert_rel(gamma_delta_ratio(150f64, 0.1), 1.6499801589220494, 4e-16);
+    assert_rel(gamma_delta_ratio(1000.5, 1e-3), 1.0069316693972253, 4e-16);
+    assert_rel(pochhammer(151.94, 17.24), 9.9592238071670745e37, 4e-15);
+    assert_rel(gamma_delta_ratio(3.3, -2.9), 0.82661125565040338, 1e-15);
+    assert_rel(gamma_delta_ratio(160.3, -155f64), 2.8208937735236958e-282, 1e-14);
+    assert_rel(gamma_delta_ratio(1e15, 0.5), 31622776.601683789, 4e-16);
+    assert_rel(gamma_delta_ratio(1e10, -3.5), 1.0000000007875e-35, 1e-15);
+    assert_eq!(gamma_delta_ratio(-3f64, 2f64), 6f64);
+}
+
+#[test]
+fn gamma_ratio_reference() {
+    assert_rel(gamma_ratio(160.3, 160.3 + 1e-10), 0.99999999949267058, 4e-16);
+    assert_rel(gamma_ratio(200.5, 200f64), 14.133299559727925, 4e-16);
+    assert_rel(gamma_ratio(1e15 + 0.5, 1e15), 31622776.601683789, 4e-16);
+    assert_rel(gamma_ratio(0.5, 3.5), 0.53333333333333333, 4e-16);
+    assert_rel(gamma_ratio(-2.5, 4.5), -0.08126984126984127, 1e-15);
+    assert_eq!(gamma_ratio(2.5, -1f64), 0f64);
+    assert!(gamma_ratio(-1f64, 2.5).is_nan());
+}
+
+#[test]
+fn beta_reference() {
+    assert_rel(beta(2.5, 3.5), 0.03681553890925539, 1e-15);
+    assert_rel(beta(1e-3, 1e3), 992.54428348605349, 1e-15);
+    assert_rel(beta(0.5, 1e10), 1.7724538509276717e-5, 1e-15);
+}
+
+#[test]
+fn lower_gamma_reference() {
+    assert_rel(lower_gamma(2.5, 1f64), 0.20053759629003473, 1e-15);
+    assert_rel(lower_gamma(0.5, 3f64), 1.7470973415820526, 1e-15);
+    assert_rel(lower_gamma(0.01, 0.5), 98.873102204949252, 1e-15);
+    assert_eq!(lower_gamma(5f64, 600f64), 24f64);
+    assert_eq!(lower_gamma(200f64, 250f64), f64::INFINITY);
+    assert_rel(ln_lower_gamma(200f64, 150f64), 848.16290884264887, 1e-15);
+    assert_rel(ln_lower_gamma(1000f64, 3000f64), 5905.2204232091812, 1e-15);
+    assert_rel(ln_lower_gamma(0.5, 1e-300), -344.69461676854691, 1e-15);
+}
+
+#[test]
+fn upper_gamma_reference() {
+    assert_rel(upper_gamma(2.5, 1f64), 1.1288027918891023, 1e-15);
+    assert_rel(upper_gamma(0.5, 3f64), 0.025356509323463443, 2e-15);
+    assert_rel(upper_gamma(5f64, 600f64), 3.4579282383358327e-250, 1e-15);
+    assert_rel(upper_gamma(0.01, 0.5), 0.55948291420134991, 1e-15);
+    assert_rel(upper_gamma(1e-10, 1e-3), 6.3315393618484102, 1e-15);
+    // Gamma(0,x) = E1(x) and negative orders
+    assert_rel(upper_gamma(0f64, 0.5), 0.55977359477616081, 1e-15);
+    assert_rel(upper_gamma(0f64, 3f64), 0.013048381094197037, 2e-15);
+    assert_rel(upper_gamma(-0.3, 0.1), 2.6033132817180211, 1e-15);
+    assert_rel(upper_gamma(-1.5, 0.7), 0.33333434409661186, 1e-15);
+    assert_rel(upper_gamma(-3.2, 2f64), 0.0026322973263967967, 4e-15);
+    assert_eq!(upper_gamma(-1f64, 0f64), f64::INFINITY);
+    assert_rel(ln_upper_gamma(200f64, 50f64), 857.93366982585744, 1e-15);
+    assert_rel(ln_upper_gamma(5f64, 1e5), -99953.948258139719, 1e-15);
+    assert_rel(ln_upper_gamma(-2.5, 1e3), -1024.1806338969401, 1e-15);
+    assert_rel(ln_upper_gamma(0.5, 0.2), -0.068020433990896291, 2e-15);
+}
+
+#[test]
+fn gammq_tails() {
+    // Small a, where 1 - P(a,x) would cancel
+    assert_rel(gammq(0.01, 0.5), 0.0056267561939671841, 1e-15);
+    assert_rel(gammq(1e-3, 1e-5), 0.010876955304217331, 1e-15);
+    assert_rel(gammq(0.1, 0.05), 0.22446136454896943, 1e-15);
+    // Large x, where exp(a ln(x) - x) would lose accuracy
+    assert_rel(gammq(5f64, 600f64), 1.4408034326399303e-251, 1e-15);
+    assert_rel(gammq(3f64, 30f64), 4.501016648012124e-11, 1e-15);
+}
+
+#[test]
+fn invgamma_reference() {
+    assert_rel(invgammp(0.5, 1f64), std::f64::consts::LN_2, 1e-15);
+    assert_rel(invgammp(0.1, 3.5), 1.416553458907672, 1e-15);
+    assert_rel(invgammp(0.9, 0.5), 1.3527717270477075, 1e-15);
+    assert_rel(invgammp(1e-10, 2f64), 1.4142202290829741e-5, 1e-15);
+    assert_rel(invgammp(0.99, 100f64), 124.7225614907208, 1e-15);
+    assert_rel(invgammp(1e-30, 0.5), 7.8539816339744844e-61, 1e-15);
+    assert_eq!(invgammp(1f64, 2f64), f64::INFINITY);
+    assert_eq!(invgammp(0f64, 2f64), 0f64);
+    // Upper tail beyond 1 - q
+    assert_rel(invgammq(1e-20, 2f64), 49.983197987090745, 1e-15);
+    assert_rel(invgammq(1e-100, 10f64), 267.80299764163213, 1e-15);
+    assert_rel(invgammq(0.3, 0.25), 0.18734822837019718, 1e-15);
+    assert_rel(invgammq(0.5, 50f64), 49.667064617994228, 1e-15);
+    assert_rel(invgammq(1e-300, 1.5), 694.16838692734289, 1e-15);
+    assert_eq!(invgammq(0f64, 2f64), f64::INFINITY);
+}
+
+#[test]
+fn gamma_regularized_large_a() {
+    let cases = [
+        (100f64, 100f64, 0.51329879827914866, 0.48670120172085134),
+        (100f64, 90f64, 0.15822098918643017, 0.84177901081356983),
+        (1e3, 1.1e3, 0.99894067674607002, 0.0010593232539299773),
+        (1e4, 9950f64, 0.30941788486118259, 0.69058211513881741),
+        (1e6, 1003000f64, 0.99863825935378241, 0.0013617406462175915),
+        (1e8, 1e8 + 1e4, 0.84134474647179881, 0.15865525352820119),
+        (1e10, 1e10 + 1e5, 0.84134474607257577, 0.15865525392742423),
+        (1e10, 1e10 - 3e5, 0.0013497798514433158, 0.99865022014855668),
+        (500f64, 400f64, 8.1093810787991598e-7, 0.99999918906189212),
+        (250f64, 320f64, 0.9999785474668728, 2.1452533127201366e-5),
+    ];
+    // Three standard deviations out, a unit roundoff in eta already costs a few ULP of the tail
+    for &(a, x, p, q) in cases.iter() {
+        assert_rel(gammp(a, x), p, 3e-15);
+        assert_rel(gammq(a, x), q, 3e-15);
+    }
+}
+
+#[test]
+fn gammp_derivative_reference() {
+    assert_rel(gammp_da(2.5, 1f64), -0.18009960695404151, 1e-15);
+    assert_rel(gammp_da(0.5, 3f64), -0.047240326447973751, 2e-15);
+    assert_rel(gammp_da(10f64, 12f64), -0.097177972037179651, 1e-15);
+    assert_rel(gammp_da(150f64, 140f64), -0.023793726651202103, 1e-15);
+    assert_rel(gammp_da(0.1, 0.01), -2.7761608124254223, 1e-15);
+    assert_rel(gammp_dx(2.5, 1f64), 0.2767383316137298, 1e-15);
+    assert_rel(gammp_dx(0.5, 3f64), 0.016217391109880487, 1e-15);
+    assert_rel(gammp_dx(10f64, 12f64), 0.087364379903049434, 1e-15);
+    assert_rel(gammp_dx(150f64, 140f64), 0.024606378364525406, 1e-15);
+    assert_rel(gammp_dx(0.1, 0.01), 6.5662343878992822, 1e-15);
+}
+
+#[test]
+fn invgamma_a_reference() {
+    assert_rel(invgammp_a(0.5, 3f64), 3.3267370107668566, 1e-15);
+    assert_rel(invgammp_a(0.05, 10f64), 15.954649323051129, 1e-15);
+    assert_rel(invgammp_a(1e-30, 2f64), 34.742067183907569, 1e-15);
+    assert_rel(invgammp_a(0.975, 5f64), 1.6772687908333324, 1e-15);
+    assert_rel(invgammq_a(1e-20, 5f64), 8.7085590818072803e-18, 2e-15);
+    assert_rel(invgammq_a(0.5, 100f64), 100.3331357413327, 1e-15);
+    assert_rel(invgammq_a(0.9, 1f64), 2.8318305266972952, 1e-15);
+    assert_eq!(invgammp_a(0f64, 1f64), f64::INFINITY);
+    assert_eq!(invgammq_a(0f64, 1f64), 0f64);
+}
+
+#[test]
+fn gammp_diff_reference() {
+    assert_rel(gammp_diff(5f64, 4.9, 5f64), 0.01771858316258292, 1e-15);
+    assert_rel(gammp_diff(100f64, 100f64, 100.001), 3.9860797439247944e-5, 1e-15);
+    assert_rel(gammp_diff(2f64, 30f64, 31f64), 1.7992704456300617e-12, 1e-15);
+    assert_rel(gammp_diff(0.5, 1e-8, 2e-8), 4.6738994763302127e-5, 1e-15);
+    assert_rel(gammp_diff(3f64, 1f64, 4f64), 0.68159529737506146, 1e-15);
+    assert_rel(gammp_diff(3f64, 4f64, 1f64), -0.68159529737506146, 1e-15);
+}